@@ -0,0 +1,662 @@
+#![no_std]
+
+use soroban_sdk::{
+    contract, contractclient, contractimpl, contracttype, token, vec, Address, Env, Symbol, Vec,
+};
+
+/// Fixed-point scale for `share_price` (7 decimal places, matching Stellar assets)
+pub const SHARE_PRICE_SCALE: i128 = 10_000_000;
+
+/// Deal lifecycle as reported by rent_payments
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DealStatus {
+    Active,
+    Completed,
+    Defaulted,
+}
+
+/// rent_payments' `DealPosition`, in the deal's token
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DealPosition {
+    pub status: DealStatus,
+    pub landlord: Address,
+    pub principal: i128,
+    pub outstanding: i128,
+}
+
+/// A deposit waiting for room under the pool's liquidity cap
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DepositRequest {
+    pub lp: Address,
+    pub amount: i128,
+}
+
+/// Shares waiting for enough idle liquidity to be redeemed
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WithdrawRequest {
+    pub lp: Address,
+    pub shares: i128,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Admin,
+    Token,
+    DealsContract,
+    MaxLiquidity,
+    Shares(Address),
+    TotalShares,
+    PendingDeposits, // Tokens held for queued deposits, not part of the pool
+    DepositQueue,
+    WithdrawQueue,
+    FundedDeals, // Funded deals, pruned by `process_queues` once they complete or default
+    Funded(u64), // Set once a deal is disbursed, so it is never funded twice
+}
+
+/// The part of rent_payments the pool reads
+#[contractclient(name = "DealsClient")]
+pub trait DealsInterface {
+    fn deal_position(env: Env, deal_id: u64) -> DealPosition;
+}
+
+#[contract]
+pub struct FundingPool;
+
+fn require_admin(env: &Env) {
+    let admin: Address = env
+        .storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .expect("admin not set");
+    admin.require_auth();
+}
+
+fn token_client(env: &Env) -> token::Client<'_> {
+    let token: Address = env.storage().instance().get(&DataKey::Token).unwrap();
+    token::Client::new(env, &token)
+}
+
+fn deals_client(env: &Env) -> DealsClient<'_> {
+    let deals: Address = env.storage().instance().get(&DataKey::DealsContract).unwrap();
+    DealsClient::new(env, &deals)
+}
+
+fn get_i128(env: &Env, key: &DataKey) -> i128 {
+    env.storage().instance().get(key).unwrap_or(0)
+}
+
+fn get_shares(env: &Env, lp: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Shares(lp.clone()))
+        .unwrap_or(0)
+}
+
+fn put_shares(env: &Env, lp: &Address, shares: i128) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::Shares(lp.clone()), &shares);
+}
+
+fn get_deposit_queue(env: &Env) -> Vec<DepositRequest> {
+    env.storage()
+        .instance()
+        .get(&DataKey::DepositQueue)
+        .unwrap_or_else(|| vec![env])
+}
+
+fn get_withdraw_queue(env: &Env) -> Vec<WithdrawRequest> {
+    env.storage()
+        .instance()
+        .get(&DataKey::WithdrawQueue)
+        .unwrap_or_else(|| vec![env])
+}
+
+fn get_funded_deals(env: &Env) -> Vec<u64> {
+    env.storage()
+        .instance()
+        .get(&DataKey::FundedDeals)
+        .unwrap_or_else(|| vec![env])
+}
+
+/// Pool tokens not lent out (excluding queued deposits)
+fn idle_liquidity(env: &Env) -> i128 {
+    token_client(env).balance(&env.current_contract_address()) - get_i128(env, &DataKey::PendingDeposits)
+}
+
+/// Principal still owed on `funded` deals, and those of them still active
+///
+/// Completed deals owe nothing and defaulted deals count for nothing. Reads
+/// only; `process_queues` drops the inactive deals from `FundedDeals`.
+fn deployed(env: &Env, funded: &Vec<u64>) -> (i128, Vec<u64>) {
+    let deals = deals_client(env);
+    let mut active = vec![env];
+    let mut deployed = 0;
+    for deal_id in funded.iter() {
+        let position = deals.deal_position(&deal_id);
+        if position.status == DealStatus::Active {
+            deployed += position.outstanding;
+            active.push_back(deal_id);
+        }
+    }
+    (deployed, active)
+}
+
+/// Idle liquidity plus the principal still owed on funded deals
+fn nav(env: &Env) -> i128 {
+    idle_liquidity(env) + deployed(env, &get_funded_deals(env)).0
+}
+
+/// Redeem queued withdrawals, then admit queued deposits, each in FIFO order
+/// for as long as liquidity allows
+///
+/// Deployed principal does not change while the queues are worked, so it is
+/// read once, and funded deals that completed or defaulted are pruned; idle
+/// liquidity is tracked as requests are settled.
+fn process_queues(env: &Env) {
+    let token = token_client(env);
+    let pool = env.current_contract_address();
+    let funded = get_funded_deals(env);
+    let (deployed, active) = deployed(env, &funded);
+    if active.len() != funded.len() {
+        env.storage().instance().set(&DataKey::FundedDeals, &active);
+    }
+    let mut idle = idle_liquidity(env);
+
+    let mut withdrawals = get_withdraw_queue(env);
+    while let Some(request) = withdrawals.first() {
+        let total_shares = get_i128(env, &DataKey::TotalShares);
+        let amount = request.shares * (idle + deployed) / total_shares;
+        if amount > idle {
+            break;
+        }
+        withdrawals.pop_front();
+        idle -= amount;
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalShares, &(total_shares - request.shares));
+        token.transfer(&pool, &request.lp, &amount);
+        env.events().publish(
+            (Symbol::new(env, "withdrawn"), request.lp),
+            (request.shares, amount),
+        );
+    }
+    env.storage()
+        .instance()
+        .set(&DataKey::WithdrawQueue, &withdrawals);
+
+    let max_liquidity: Option<i128> = env.storage().instance().get(&DataKey::MaxLiquidity);
+    let mut deposits = get_deposit_queue(env);
+    while let Some(request) = deposits.first() {
+        if max_liquidity.is_some_and(|max| idle + request.amount > max) {
+            break;
+        }
+        let total_shares = get_i128(env, &DataKey::TotalShares);
+        let nav = idle + deployed;
+        let minted = if total_shares == 0 {
+            request.amount
+        } else if nav > 0 {
+            request.amount * total_shares / nav
+        } else {
+            // Every share is worth nothing; wait rather than mint without bound
+            break;
+        };
+        deposits.pop_front();
+        idle += request.amount;
+        let pending = get_i128(env, &DataKey::PendingDeposits) - request.amount;
+        env.storage().instance().set(&DataKey::PendingDeposits, &pending);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalShares, &(total_shares + minted));
+        put_shares(env, &request.lp, get_shares(env, &request.lp) + minted);
+        env.events().publish(
+            (Symbol::new(env, "deposited"), request.lp),
+            (request.amount, minted),
+        );
+    }
+    env.storage().instance().set(&DataKey::DepositQueue, &deposits);
+}
+
+#[contractimpl]
+impl FundingPool {
+    /// `token` is the pool asset (e.g. USDC) and `deals_contract` the
+    /// rent_payments contract whose deals the pool finances
+    pub fn init(env: Env, admin: Address, token: Address, deals_contract: Address) {
+        if env.storage().instance().has(&DataKey::Admin) {
+            panic!("already initialized")
+        }
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::Token, &token);
+        env.storage()
+            .instance()
+            .set(&DataKey::DealsContract, &deals_contract);
+        env.events().publish((Symbol::new(&env, "init"),), admin);
+    }
+
+    /// Cap idle liquidity; deposits that would exceed it wait in the deposit
+    /// queue until deals are funded (admin-auth)
+    pub fn set_max_liquidity(env: Env, max_liquidity: Option<i128>) {
+        require_admin(&env);
+        if let Some(max) = max_liquidity {
+            if max <= 0 {
+                panic!("max_liquidity must be positive")
+            }
+            env.storage().instance().set(&DataKey::MaxLiquidity, &max);
+        } else {
+            env.storage().instance().remove(&DataKey::MaxLiquidity);
+        }
+        env.events()
+            .publish((Symbol::new(&env, "set_max_liquidity"),), max_liquidity);
+        process_queues(&env);
+    }
+
+    /// Deposit tokens for pool shares at the current share price (lp-auth)
+    ///
+    /// The tokens are taken immediately; shares are minted once the deposit
+    /// reaches the front of the deposit queue and fits under the liquidity cap.
+    pub fn deposit(env: Env, lp: Address, amount: i128) {
+        lp.require_auth();
+        if amount <= 0 {
+            panic!("amount must be positive")
+        }
+        token_client(&env).transfer(&lp, &env.current_contract_address(), &amount);
+        env.storage().instance().set(
+            &DataKey::PendingDeposits,
+            &(get_i128(&env, &DataKey::PendingDeposits) + amount),
+        );
+
+        let mut deposits = get_deposit_queue(&env);
+        deposits.push_back(DepositRequest {
+            lp: lp.clone(),
+            amount,
+        });
+        env.storage().instance().set(&DataKey::DepositQueue, &deposits);
+        env.events()
+            .publish((Symbol::new(&env, "deposit_queued"), lp), amount);
+        process_queues(&env);
+    }
+
+    /// Redeem shares for tokens at the share price when paid out (lp-auth)
+    ///
+    /// The shares are locked immediately; they are redeemed once the request
+    /// reaches the front of the withdraw queue and enough liquidity is idle.
+    pub fn request_withdraw(env: Env, lp: Address, shares: i128) {
+        lp.require_auth();
+        if shares <= 0 {
+            panic!("shares must be positive")
+        }
+        let balance = get_shares(&env, &lp);
+        if shares > balance {
+            panic!("insufficient shares")
+        }
+        put_shares(&env, &lp, balance - shares);
+
+        let mut withdrawals = get_withdraw_queue(&env);
+        withdrawals.push_back(WithdrawRequest {
+            lp: lp.clone(),
+            shares,
+        });
+        env.storage()
+            .instance()
+            .set(&DataKey::WithdrawQueue, &withdrawals);
+        env.events()
+            .publish((Symbol::new(&env, "withdraw_requested"), lp), shares);
+        process_queues(&env);
+    }
+
+    /// Work through the queues after liquidity changes, e.g. repayments
+    pub fn process_queues(env: Env) {
+        process_queues(&env);
+    }
+
+    /// Pay an active deal's principal to its landlord (admin-auth)
+    ///
+    /// The deal's payment recipient in rent_payments should be this pool, so
+    /// that tenant repayments flow back in. NGN-denominated deals are paid, and
+    /// then valued, at the oracle's current rate.
+    pub fn disburse(env: Env, deal_id: u64) {
+        require_admin(&env);
+        let mut funded = get_funded_deals(&env);
+        if funded.contains(deal_id) || env.storage().persistent().has(&DataKey::Funded(deal_id)) {
+            panic!("deal already funded")
+        }
+        let position = deals_client(&env).deal_position(&deal_id);
+        if position.status != DealStatus::Active {
+            panic!("deal is not active")
+        }
+        if position.principal > idle_liquidity(&env) {
+            panic!("insufficient liquidity")
+        }
+
+        funded.push_back(deal_id);
+        env.storage().instance().set(&DataKey::FundedDeals, &funded);
+        env.storage().persistent().set(&DataKey::Funded(deal_id), &true);
+        token_client(&env).transfer(
+            &env.current_contract_address(),
+            &position.landlord,
+            &position.principal,
+        );
+        env.events().publish(
+            (Symbol::new(&env, "deal_funded"), deal_id),
+            (position.landlord, position.principal),
+        );
+        process_queues(&env);
+    }
+
+    pub fn shares(env: Env, lp: Address) -> i128 {
+        get_shares(&env, &lp)
+    }
+
+    /// All shares in issue, including those queued for withdrawal
+    pub fn total_shares(env: Env) -> i128 {
+        get_i128(&env, &DataKey::TotalShares)
+    }
+
+    pub fn nav(env: Env) -> i128 {
+        nav(&env)
+    }
+
+    /// Value of one share, scaled by `SHARE_PRICE_SCALE`
+    pub fn share_price(env: Env) -> i128 {
+        let total_shares = get_i128(&env, &DataKey::TotalShares);
+        if total_shares == 0 {
+            return SHARE_PRICE_SCALE;
+        }
+        nav(&env) * SHARE_PRICE_SCALE / total_shares
+    }
+
+    pub fn idle_liquidity(env: Env) -> i128 {
+        idle_liquidity(&env)
+    }
+
+    /// Funded deals not yet pruned; completed or defaulted ones are dropped by
+    /// the next `process_queues`
+    pub fn funded_deals(env: Env) -> Vec<u64> {
+        get_funded_deals(&env)
+    }
+
+    pub fn deposit_queue(env: Env) -> Vec<DepositRequest> {
+        get_deposit_queue(&env)
+    }
+
+    pub fn withdraw_queue(env: Env) -> Vec<WithdrawRequest> {
+        get_withdraw_queue(&env)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::{FundingPool, FundingPoolClient, SHARE_PRICE_SCALE};
+    use rent_payments::RentPaymentsClient;
+    use soroban_sdk::testutils::{Address as _, Ledger, MockAuth, MockAuthInvoke};
+    use soroban_sdk::token::{Client as TokenClient, StellarAssetClient};
+    use soroban_sdk::{Address, Env, IntoVal, Symbol};
+
+    const DAY: u64 = 24 * 60 * 60;
+    const NGN_USDC: &str = "NGN_USDC";
+
+    /// A pool financing deal 1 (1200 over 12 installments), whose repayments
+    /// are paid to the pool; returns the pool, rent_payments, the token and the
+    /// tenant
+    fn setup(
+        env: &Env,
+    ) -> (FundingPoolClient<'_>, RentPaymentsClient<'_>, TokenClient<'_>, Address) {
+        let admin = Address::generate(env);
+        let token_id = env
+            .register_stellar_asset_contract_v2(Address::generate(env))
+            .address();
+        let deals_id = env.register(rent_payments::RentPayments, ());
+        let pool_id = env.register(FundingPool, ());
+        let deals = RentPaymentsClient::new(env, &deals_id);
+        let pool = FundingPoolClient::new(env, &pool_id);
+        let tenant = Address::generate(env);
+
+        env.mock_all_auths();
+        deals.init(&admin);
+        deals.create_deal(&1u64, &tenant, &Address::generate(env), &1200, &12, &(30 * DAY));
+        deals.set_deal_payment(&1u64, &token_id, &Some(pool_id.clone()));
+        pool.init(&admin, &token_id, &deals_id);
+
+        StellarAssetClient::new(env, &token_id).mint(&tenant, &2000);
+        (pool, deals, TokenClient::new(env, &token_id), tenant)
+    }
+
+    fn new_lp(env: &Env, token: &TokenClient<'_>, amount: i128) -> Address {
+        let lp = Address::generate(env);
+        StellarAssetClient::new(env, &token.address).mint(&lp, &amount);
+        lp
+    }
+
+    /// An oracle quoting ₦1,500 per token for `NGN_USDC`
+    fn ngn_oracle(env: &Env) -> Address {
+        let oracle_id = env.register(fx_oracle::FxOracle, ());
+        let oracle = fx_oracle::FxOracleClient::new(env, &oracle_id);
+        let reporter = Address::generate(env);
+        oracle.init(&Address::generate(env));
+        oracle.add_reporter(&reporter);
+        let rate = 1_500 * fx_oracle::RATE_SCALE;
+        let pair = Symbol::new(env, NGN_USDC);
+        oracle.submit_rate(&reporter, &pair, &rate, &env.ledger().timestamp());
+        oracle_id
+    }
+
+    // ============================================================================
+    // Deposit Tests
+    // ============================================================================
+
+    #[test]
+    fn deposit_mints_shares_at_share_price() {
+        let env = Env::default();
+        let (pool, _deals, token, _tenant) = setup(&env);
+        let first = new_lp(&env, &token, 1000);
+        let second = new_lp(&env, &token, 500);
+
+        pool.deposit(&first, &1000);
+        pool.deposit(&second, &500);
+
+        assert_eq!(pool.shares(&first), 1000);
+        assert_eq!(pool.shares(&second), 500);
+        assert_eq!(pool.total_shares(), 1500);
+        assert_eq!(pool.nav(), 1500);
+        assert_eq!(pool.share_price(), SHARE_PRICE_SCALE);
+    }
+
+    #[test]
+    #[should_panic]
+    fn deposit_requires_lp_auth() {
+        let env = Env::default();
+        let (pool, _deals, token, tenant) = setup(&env);
+        let lp = new_lp(&env, &token, 1000);
+
+        env.mock_auths(&[MockAuth {
+            address: &tenant,
+            invoke: &MockAuthInvoke {
+                contract: &pool.address,
+                fn_name: "deposit",
+                args: (lp.clone(), 1000i128).into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+        pool.deposit(&lp, &1000);
+    }
+
+    #[test]
+    fn deposit_waits_under_max_liquidity() {
+        let env = Env::default();
+        let (pool, _deals, token, _tenant) = setup(&env);
+        let first = new_lp(&env, &token, 1200);
+        let second = new_lp(&env, &token, 500);
+        pool.set_max_liquidity(&Some(1500));
+
+        pool.deposit(&first, &1200);
+        pool.deposit(&second, &500);
+        assert_eq!(pool.shares(&second), 0);
+        assert_eq!(pool.deposit_queue().len(), 1);
+        assert_eq!(pool.idle_liquidity(), 1200);
+
+        // Funding the deal makes room for the queued deposit
+        pool.disburse(&1u64);
+        assert_eq!(pool.shares(&second), 500);
+        assert_eq!(pool.deposit_queue().len(), 0);
+    }
+
+    // ============================================================================
+    // Financing Tests
+    // ============================================================================
+
+    #[test]
+    fn disburse_pays_landlord_and_keeps_nav() {
+        let env = Env::default();
+        let (pool, deals, token, _tenant) = setup(&env);
+        let lp = new_lp(&env, &token, 2000);
+        pool.deposit(&lp, &2000);
+
+        pool.disburse(&1u64);
+
+        assert_eq!(token.balance(&deals.get_deal(&1u64).landlord), 1200);
+        assert_eq!(pool.idle_liquidity(), 800);
+        assert_eq!(pool.nav(), 2000);
+        assert_eq!(pool.share_price(), SHARE_PRICE_SCALE);
+    }
+
+    #[test]
+    #[should_panic(expected = "insufficient liquidity")]
+    fn disburse_requires_liquidity() {
+        let env = Env::default();
+        let (pool, _deals, token, _tenant) = setup(&env);
+        let lp = new_lp(&env, &token, 1000);
+        pool.deposit(&lp, &1000);
+
+        pool.disburse(&1u64);
+    }
+
+    #[test]
+    fn disburse_pays_ngn_deals_in_tokens() {
+        let env = Env::default();
+        let (pool, deals, token, tenant) = setup(&env);
+        let landlord = Address::generate(&env);
+        // ₦3,000 over 3 installments, in kobo
+        deals.create_deal(&2u64, &tenant, &landlord, &300_000, &3, &(30 * DAY));
+        deals.set_deal_payment(&2u64, &token.address, &Some(pool.address.clone()));
+        let pair = Symbol::new(&env, NGN_USDC);
+        deals.set_deal_fx(&2u64, &ngn_oracle(&env), &pair, &DAY, &1u32);
+        let lp = new_lp(&env, &token, 30_000_000);
+        pool.deposit(&lp, &30_000_000);
+
+        pool.disburse(&2u64);
+        // ₦3,000 at ₦1,500 per token
+        assert_eq!(token.balance(&landlord), 20_000_000);
+        assert_eq!(pool.nav(), 30_000_000);
+
+        StellarAssetClient::new(&env, &token.address).mint(&tenant, &10_000_000);
+        deals.pay(&2u64, &tenant, &100_000);
+        // 6_666_667 repaid and ₦2,000 (13_333_334) still deployed, both rounded up
+        assert_eq!(pool.idle_liquidity(), 16_666_667);
+        assert_eq!(pool.nav(), 30_000_001);
+    }
+
+    #[test]
+    fn repayments_and_defaults_move_share_price() {
+        let env = Env::default();
+        let (pool, deals, token, tenant) = setup(&env);
+        let lp = new_lp(&env, &token, 2000);
+        pool.deposit(&lp, &2000);
+        pool.disburse(&1u64);
+
+        // A late fee paid to the pool is income for its LPs
+        deals.assess_fee(&1u64, &100, &Symbol::new(&env, "late_fee"));
+        env.ledger().set_timestamp(30 * DAY);
+        deals.pay(&1u64, &tenant, &200);
+        assert_eq!(pool.nav(), 2100);
+        assert_eq!(pool.share_price(), 2100 * SHARE_PRICE_SCALE / 2000);
+
+        // A default writes off the principal still owed
+        deals.mark_default(&1u64);
+        assert_eq!(pool.nav(), 1000);
+        assert_eq!(pool.share_price(), SHARE_PRICE_SCALE / 2);
+    }
+
+    #[test]
+    fn settled_deals_leave_the_nav() {
+        let env = Env::default();
+        let (pool, deals, token, tenant) = setup(&env);
+        let lp = new_lp(&env, &token, 2000);
+        pool.deposit(&lp, &2000);
+        pool.disburse(&1u64);
+        assert_eq!(pool.funded_deals().len(), 1);
+
+        env.ledger().set_timestamp(30 * DAY);
+        deals.pay(&1u64, &tenant, &1200);
+        // Reading the NAV leaves the list alone
+        assert_eq!(pool.nav(), 2000);
+        assert_eq!(pool.funded_deals().len(), 1);
+
+        pool.process_queues();
+        assert_eq!(pool.funded_deals().len(), 0);
+        assert_eq!(pool.nav(), 2000);
+    }
+
+    #[test]
+    #[should_panic(expected = "deal already funded")]
+    fn pruned_deal_cannot_be_funded_again() {
+        let env = Env::default();
+        let (pool, deals, token, tenant) = setup(&env);
+        let lp = new_lp(&env, &token, 2000);
+        pool.deposit(&lp, &2000);
+        pool.disburse(&1u64);
+        env.ledger().set_timestamp(30 * DAY);
+        let receipt = deals.pay(&1u64, &tenant, &1200);
+        pool.process_queues();
+
+        // A reversal reopens the deal after the pool stopped tracking it
+        let reason = Symbol::new(&env, "chargeback");
+        let tx_id = soroban_sdk::BytesN::from_array(&env, &[0xCC; 32]);
+        deals.reverse_receipt(&1u64, &receipt.id, &reason, &tx_id);
+        pool.disburse(&1u64);
+    }
+
+    // ============================================================================
+    // Withdrawal Tests
+    // ============================================================================
+
+    #[test]
+    fn withdraw_waits_for_repayments() {
+        let env = Env::default();
+        let (pool, deals, token, tenant) = setup(&env);
+        let lp = new_lp(&env, &token, 1200);
+        pool.deposit(&lp, &1200);
+        pool.disburse(&1u64);
+
+        pool.request_withdraw(&lp, &600);
+        assert_eq!(pool.shares(&lp), 600);
+        assert_eq!(pool.withdraw_queue().len(), 1);
+        assert_eq!(token.balance(&lp), 0);
+
+        env.ledger().set_timestamp(30 * DAY);
+        deals.pay(&1u64, &tenant, &600);
+        pool.process_queues();
+
+        assert_eq!(token.balance(&lp), 600);
+        assert_eq!(pool.withdraw_queue().len(), 0);
+        assert_eq!(pool.total_shares(), 600);
+        assert_eq!(pool.nav(), 600);
+    }
+
+    #[test]
+    #[should_panic(expected = "insufficient shares")]
+    fn withdraw_requires_shares() {
+        let env = Env::default();
+        let (pool, _deals, token, _tenant) = setup(&env);
+        let lp = new_lp(&env, &token, 100);
+        pool.deposit(&lp, &100);
+
+        pool.request_withdraw(&lp, &101);
+    }
+}
@@ -0,0 +1,339 @@
+#![no_std]
+
+use soroban_sdk::{contract, contractimpl, contracttype, vec, Address, Env, Symbol, Vec};
+
+/// Fixed-point scale for rates (7 decimal places, matching Stellar assets)
+pub const RATE_SCALE: i128 = 10_000_000;
+
+/// A reporter's latest rate for a pair, e.g. NGN per whole USDC scaled by
+/// `RATE_SCALE` for `NGN_USDC`
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Report {
+    pub reporter: Address,
+    pub rate: i128,
+    pub timestamp: u64, // When the reporter observed the rate
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Admin,
+    Reporters,
+    Reports(Symbol), // Latest report per reporter, by pair
+}
+
+#[contract]
+pub struct FxOracle;
+
+fn require_admin(env: &Env) {
+    let admin: Address = env
+        .storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .expect("admin not set");
+    admin.require_auth();
+}
+
+fn get_reporters(env: &Env) -> Vec<Address> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Reporters)
+        .unwrap_or_else(|| vec![env])
+}
+
+fn get_reports(env: &Env, pair: &Symbol) -> Vec<Report> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Reports(pair.clone()))
+        .unwrap_or_else(|| vec![env])
+}
+
+/// Reports for `pair` observed within `max_age` seconds of now
+fn fresh_reports(env: &Env, pair: &Symbol, max_age: u64) -> Vec<Report> {
+    let now = env.ledger().timestamp();
+    let mut fresh = vec![env];
+    for report in get_reports(env, pair).iter() {
+        if now - report.timestamp <= max_age {
+            fresh.push_back(report);
+        }
+    }
+    fresh
+}
+
+#[contractimpl]
+impl FxOracle {
+    pub fn init(env: Env, admin: Address) {
+        if env.storage().instance().has(&DataKey::Admin) {
+            panic!("already initialized")
+        }
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.events().publish((Symbol::new(&env, "init"),), admin);
+    }
+
+    /// Allow `reporter` to submit rates (admin-auth)
+    pub fn add_reporter(env: Env, reporter: Address) {
+        require_admin(&env);
+        let mut reporters = get_reporters(&env);
+        if reporters.contains(&reporter) {
+            panic!("reporter already added")
+        }
+        reporters.push_back(reporter.clone());
+        env.storage().instance().set(&DataKey::Reporters, &reporters);
+        env.events()
+            .publish((Symbol::new(&env, "add_reporter"),), reporter);
+    }
+
+    /// Stop accepting rates from `reporter` (admin-auth)
+    ///
+    /// Reports it already submitted are ignored from then on.
+    pub fn remove_reporter(env: Env, reporter: Address) {
+        require_admin(&env);
+        let mut reporters = get_reporters(&env);
+        let index = reporters
+            .first_index_of(&reporter)
+            .expect("reporter not found");
+        reporters.remove(index);
+        env.storage().instance().set(&DataKey::Reporters, &reporters);
+        env.events()
+            .publish((Symbol::new(&env, "remove_reporter"),), reporter);
+    }
+
+    pub fn reporters(env: Env) -> Vec<Address> {
+        get_reporters(&env)
+    }
+
+    /// Submit a rate for `pair` observed at `timestamp` (reporter-auth)
+    ///
+    /// Replaces the reporter's previous report for the pair, which must be
+    /// older.
+    pub fn submit_rate(env: Env, reporter: Address, pair: Symbol, rate: i128, timestamp: u64) {
+        reporter.require_auth();
+        if !get_reporters(&env).contains(&reporter) {
+            panic!("not a reporter")
+        }
+        if rate <= 0 {
+            panic!("rate must be positive")
+        }
+        if timestamp > env.ledger().timestamp() {
+            panic!("timestamp is in the future")
+        }
+
+        let mut reports = get_reports(&env, &pair);
+        if let Some(index) = reports.iter().position(|r| r.reporter == reporter) {
+            if reports.get_unchecked(index as u32).timestamp >= timestamp {
+                panic!("report is not newer than the last one")
+            }
+            reports.remove(index as u32);
+        }
+        reports.push_back(Report {
+            reporter: reporter.clone(),
+            rate,
+            timestamp,
+        });
+        env.storage()
+            .persistent()
+            .set(&DataKey::Reports(pair.clone()), &reports);
+        env.events().publish(
+            (Symbol::new(&env, "rate_submitted"), pair, reporter),
+            (rate, timestamp),
+        );
+    }
+
+    /// Most recent report for `pair` from a current reporter; panics if it is
+    /// older than `max_age` seconds
+    pub fn latest(env: Env, pair: Symbol, max_age: u64) -> Report {
+        let reporters = get_reporters(&env);
+        let mut latest: Option<Report> = None;
+        for report in fresh_reports(&env, &pair, max_age).iter() {
+            if reporters.contains(&report.reporter)
+                && latest.as_ref().is_none_or(|l| report.timestamp > l.timestamp)
+            {
+                latest = Some(report);
+            }
+        }
+        latest.expect("no fresh rate")
+    }
+
+    /// Median of current reporters' rates for `pair` that are at most
+    /// `max_age` seconds old; panics unless at least `min_reports` qualify
+    ///
+    /// With an even number of reports, the two middle rates are averaged.
+    pub fn median(env: Env, pair: Symbol, max_age: u64, min_reports: u32) -> i128 {
+        let reporters = get_reporters(&env);
+        let mut rates: Vec<i128> = vec![&env];
+        for report in fresh_reports(&env, &pair, max_age).iter() {
+            if !reporters.contains(&report.reporter) {
+                continue;
+            }
+            // Insertion sort; there is one report per reporter
+            let index = rates
+                .iter()
+                .position(|r| r > report.rate)
+                .map_or(rates.len(), |i| i as u32);
+            rates.insert(index, report.rate);
+        }
+        if rates.is_empty() || rates.len() < min_reports {
+            panic!("not enough fresh reports")
+        }
+
+        let mid = rates.len() / 2;
+        if rates.len() % 2 == 1 {
+            rates.get_unchecked(mid)
+        } else {
+            (rates.get_unchecked(mid - 1) + rates.get_unchecked(mid)) / 2
+        }
+    }
+
+    /// Every stored report for `pair`, including stale ones
+    pub fn reports(env: Env, pair: Symbol) -> Vec<Report> {
+        get_reports(&env, &pair)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::{FxOracle, FxOracleClient, RATE_SCALE};
+    use soroban_sdk::testutils::{Address as _, Ledger, MockAuth, MockAuthInvoke};
+    use soroban_sdk::{Address, Env, IntoVal, Symbol};
+
+    const HOUR: u64 = 60 * 60;
+
+    fn setup(env: &Env) -> (FxOracleClient<'_>, Address, Symbol) {
+        let contract_id = env.register(FxOracle, ());
+        let client = FxOracleClient::new(env, &contract_id);
+        env.ledger().set_timestamp(10 * HOUR);
+        env.mock_all_auths();
+        client.init(&Address::generate(env));
+        (client, contract_id, Symbol::new(env, "NGN_USDC"))
+    }
+
+    fn add_reporters(env: &Env, client: &FxOracleClient<'_>, count: u32) -> std::vec::Vec<Address> {
+        (0..count)
+            .map(|_| {
+                let reporter = Address::generate(env);
+                client.add_reporter(&reporter);
+                reporter
+            })
+            .collect()
+    }
+
+    // ============================================================================
+    // Reporting Tests
+    // ============================================================================
+
+    #[test]
+    fn submit_rate_replaces_reporters_previous_report() {
+        let env = Env::default();
+        let (client, _, pair) = setup(&env);
+        let reporters = add_reporters(&env, &client, 1);
+
+        client.submit_rate(&reporters[0], &pair, &(1_500 * RATE_SCALE), &(9 * HOUR));
+        client.submit_rate(&reporters[0], &pair, &(1_520 * RATE_SCALE), &(10 * HOUR));
+
+        let reports = client.reports(&pair);
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports.get_unchecked(0).rate, 1_520 * RATE_SCALE);
+    }
+
+    #[test]
+    #[should_panic]
+    fn submit_rate_requires_reporter_auth() {
+        let env = Env::default();
+        let (client, contract_id, pair) = setup(&env);
+        let reporters = add_reporters(&env, &client, 1);
+        let other = Address::generate(&env);
+
+        env.mock_auths(&[MockAuth {
+            address: &other,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "submit_rate",
+                args: (reporters[0].clone(), pair.clone(), RATE_SCALE, 9 * HOUR).into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+        client.submit_rate(&reporters[0], &pair, &RATE_SCALE, &(9 * HOUR));
+    }
+
+    #[test]
+    #[should_panic(expected = "not a reporter")]
+    fn submit_rate_rejects_unknown_reporter() {
+        let env = Env::default();
+        let (client, _, pair) = setup(&env);
+
+        client.submit_rate(&Address::generate(&env), &pair, &RATE_SCALE, &(9 * HOUR));
+    }
+
+    #[test]
+    #[should_panic(expected = "report is not newer than the last one")]
+    fn submit_rate_rejects_older_report() {
+        let env = Env::default();
+        let (client, _, pair) = setup(&env);
+        let reporters = add_reporters(&env, &client, 1);
+
+        client.submit_rate(&reporters[0], &pair, &RATE_SCALE, &(9 * HOUR));
+        client.submit_rate(&reporters[0], &pair, &RATE_SCALE, &(8 * HOUR));
+    }
+
+    // ============================================================================
+    // Reading Tests
+    // ============================================================================
+
+    #[test]
+    fn latest_returns_most_recent_fresh_report() {
+        let env = Env::default();
+        let (client, _, pair) = setup(&env);
+        let reporters = add_reporters(&env, &client, 2);
+        client.submit_rate(&reporters[0], &pair, &(1_500 * RATE_SCALE), &(9 * HOUR));
+        client.submit_rate(&reporters[1], &pair, &(1_510 * RATE_SCALE), &(10 * HOUR - 60));
+
+        let latest = client.latest(&pair, &HOUR);
+        assert_eq!(latest.reporter, reporters[1]);
+        assert_eq!(latest.rate, 1_510 * RATE_SCALE);
+    }
+
+    #[test]
+    #[should_panic(expected = "no fresh rate")]
+    fn latest_enforces_max_age() {
+        let env = Env::default();
+        let (client, _, pair) = setup(&env);
+        let reporters = add_reporters(&env, &client, 1);
+        client.submit_rate(&reporters[0], &pair, &RATE_SCALE, &(8 * HOUR));
+
+        client.latest(&pair, &HOUR);
+    }
+
+    #[test]
+    fn median_ignores_stale_and_removed_reporters() {
+        let env = Env::default();
+        let (client, _, pair) = setup(&env);
+        let reporters = add_reporters(&env, &client, 5);
+        client.submit_rate(&reporters[0], &pair, &1_530, &(10 * HOUR));
+        client.submit_rate(&reporters[1], &pair, &1_500, &(10 * HOUR));
+        client.submit_rate(&reporters[2], &pair, &1_510, &(10 * HOUR));
+        client.submit_rate(&reporters[3], &pair, &9_999, &(5 * HOUR));
+        client.submit_rate(&reporters[4], &pair, &1, &(10 * HOUR));
+
+        client.remove_reporter(&reporters[4]);
+        assert_eq!(client.median(&pair, &HOUR, &3), 1_510);
+
+        // An even number of reports averages the middle two
+        client.submit_rate(&reporters[3], &pair, &1_540, &(10 * HOUR));
+        assert_eq!(client.median(&pair, &HOUR, &3), 1_520);
+    }
+
+    #[test]
+    #[should_panic(expected = "not enough fresh reports")]
+    fn median_requires_min_reports() {
+        let env = Env::default();
+        let (client, _, pair) = setup(&env);
+        let reporters = add_reporters(&env, &client, 2);
+        client.submit_rate(&reporters[0], &pair, &1_500, &(10 * HOUR));
+        client.submit_rate(&reporters[1], &pair, &1_510, &(7 * HOUR));
+
+        client.median(&pair, &HOUR, &2);
+    }
+}
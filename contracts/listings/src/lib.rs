@@ -0,0 +1,402 @@
+#![no_std]
+
+use soroban_sdk::{contract, contractimpl, contracttype, Address, BytesN, Env, Symbol};
+
+/// Listing ID type
+pub type ListingId = u64;
+
+/// Timestamp type (Unix timestamp in seconds)
+pub type Timestamp = u64;
+
+/// Review state of a listing; mirrors the backend's `ListingStatus`
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ListingStatus {
+    PendingReview,
+    Approved,
+    Rejected,
+    Rented,
+}
+
+/// A property reported by a whistleblower
+///
+/// Listing details stay off-chain; `listing_hash` is the SHA-256 of the
+/// canonical listing document (address, landlord, annual rent and photos hash)
+/// so anyone holding it can check it against the chain.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Listing {
+    pub id: ListingId,
+    pub whistleblower: Address,
+    pub listing_hash: BytesN<32>,
+    pub status: ListingStatus,
+    pub submitted_at: Timestamp,
+    pub updated_at: Timestamp,
+    pub rejection_reason: Option<Symbol>, // Set when rejected
+    pub deal_id: Option<u64>,             // rent_payments deal, set when rented
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Admin,
+    DealsContract,
+    ListingCount,
+    Listing(ListingId),
+    ListingByHash(BytesN<32>),
+}
+
+#[contract]
+pub struct Listings;
+
+fn require_admin(env: &Env) {
+    let admin: Address = env
+        .storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .expect("admin not set");
+    admin.require_auth();
+}
+
+fn get_listing(env: &Env, listing_id: ListingId) -> Listing {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Listing(listing_id))
+        .expect("listing not found")
+}
+
+fn put_listing(env: &Env, listing: &Listing) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::Listing(listing.id), listing);
+}
+
+/// Move a listing out of review, stamping the change
+fn review(env: &Env, listing_id: ListingId, status: ListingStatus) -> Listing {
+    let mut listing = get_listing(env, listing_id);
+    if listing.status != ListingStatus::PendingReview {
+        panic!("listing is not pending review");
+    }
+    listing.status = status;
+    listing.updated_at = env.ledger().timestamp();
+    listing
+}
+
+#[contractimpl]
+impl Listings {
+    pub fn init(env: Env, admin: Address) {
+        if env.storage().instance().has(&DataKey::Admin) {
+            panic!("already initialized")
+        }
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.events().publish((Symbol::new(&env, "init"),), admin);
+    }
+
+    /// Set the rent_payments contract allowed to mark listings as rented
+    pub fn set_deals_contract(env: Env, deals_contract: Address) {
+        require_admin(&env);
+        env.storage()
+            .instance()
+            .set(&DataKey::DealsContract, &deals_contract);
+        env.events()
+            .publish((Symbol::new(&env, "set_deals_contract"),), deals_contract);
+    }
+
+    /// Submit a listing for review (whistleblower-auth)
+    pub fn submit_listing(env: Env, whistleblower: Address, listing_hash: BytesN<32>) -> ListingId {
+        whistleblower.require_auth();
+
+        let by_hash = DataKey::ListingByHash(listing_hash.clone());
+        if env.storage().persistent().has(&by_hash) {
+            panic!("listing already submitted");
+        }
+
+        let listing_id: ListingId = env
+            .storage()
+            .instance()
+            .get(&DataKey::ListingCount)
+            .unwrap_or(0)
+            + 1;
+        env.storage()
+            .instance()
+            .set(&DataKey::ListingCount, &listing_id);
+
+        let now = env.ledger().timestamp();
+        put_listing(
+            &env,
+            &Listing {
+                id: listing_id,
+                whistleblower: whistleblower.clone(),
+                listing_hash: listing_hash.clone(),
+                status: ListingStatus::PendingReview,
+                submitted_at: now,
+                updated_at: now,
+                rejection_reason: None,
+                deal_id: None,
+            },
+        );
+        env.storage().persistent().set(&by_hash, &listing_id);
+
+        env.events().publish(
+            (Symbol::new(&env, "listing_submitted"), listing_id),
+            (whistleblower, listing_hash),
+        );
+        listing_id
+    }
+
+    /// Mark a pending listing as verified (admin-auth)
+    pub fn approve_listing(env: Env, listing_id: ListingId) {
+        require_admin(&env);
+        let listing = review(&env, listing_id, ListingStatus::Approved);
+        put_listing(&env, &listing);
+        env.events()
+            .publish((Symbol::new(&env, "listing_approved"), listing_id), ());
+    }
+
+    /// Reject a pending listing (admin-auth)
+    pub fn reject_listing(env: Env, listing_id: ListingId, reason: Symbol) {
+        require_admin(&env);
+        let mut listing = review(&env, listing_id, ListingStatus::Rejected);
+        listing.rejection_reason = Some(reason.clone());
+        put_listing(&env, &listing);
+        env.events()
+            .publish((Symbol::new(&env, "listing_rejected"), listing_id), reason);
+    }
+
+    /// Record that an approved listing was financed by a deal
+    ///
+    /// Only the configured deals contract can call this, which it does when a
+    /// deal is linked to the listing.
+    pub fn mark_rented(env: Env, listing_id: ListingId, deal_id: u64) {
+        let deals_contract: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::DealsContract)
+            .expect("deals contract not set");
+        deals_contract.require_auth();
+
+        let mut listing = get_listing(&env, listing_id);
+        if listing.status != ListingStatus::Approved {
+            panic!("listing is not approved");
+        }
+        listing.status = ListingStatus::Rented;
+        listing.deal_id = Some(deal_id);
+        listing.updated_at = env.ledger().timestamp();
+        put_listing(&env, &listing);
+
+        env.events()
+            .publish((Symbol::new(&env, "listing_rented"), listing_id), deal_id);
+    }
+
+    pub fn get_listing(env: Env, listing_id: ListingId) -> Listing {
+        get_listing(&env, listing_id)
+    }
+
+    pub fn get_listing_by_hash(env: Env, listing_hash: BytesN<32>) -> Option<Listing> {
+        env.storage()
+            .persistent()
+            .get::<_, ListingId>(&DataKey::ListingByHash(listing_hash))
+            .map(|listing_id| get_listing(&env, listing_id))
+    }
+
+    pub fn listing_count(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::ListingCount)
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::{Listing, ListingStatus, Listings, ListingsClient};
+    use soroban_sdk::testutils::{Address as _, Events, Ledger, MockAuth, MockAuthInvoke};
+    use soroban_sdk::{Address, BytesN, Env, IntoVal, Symbol};
+
+    fn setup(env: &Env) -> (Address, ListingsClient<'_>, Address) {
+        let contract_id = env.register(Listings, ());
+        let client = ListingsClient::new(env, &contract_id);
+        let admin = Address::generate(env);
+        client.init(&admin);
+        (contract_id, client, admin)
+    }
+
+    fn submit(env: &Env, client: &ListingsClient<'_>, whistleblower: &Address, seed: u8) -> u64 {
+        env.mock_all_auths();
+        client.submit_listing(whistleblower, &BytesN::from_array(env, &[seed; 32]))
+    }
+
+    fn approve(env: &Env, client: &ListingsClient<'_>, contract_id: &Address, admin: &Address, listing_id: u64) {
+        env.mock_auths(&[MockAuth {
+            address: admin,
+            invoke: &MockAuthInvoke {
+                contract: contract_id,
+                fn_name: "approve_listing",
+                args: (listing_id,).into_val(env),
+                sub_invokes: &[],
+            },
+        }]);
+        client.approve_listing(&listing_id);
+    }
+
+    // ============================================================================
+    // Submission Tests
+    // ============================================================================
+
+    #[test]
+    fn submit_listing_starts_pending_review() {
+        let env = Env::default();
+        let (_, client, _) = setup(&env);
+        let whistleblower = Address::generate(&env);
+        env.ledger().set_timestamp(1_000);
+
+        let listing_id = submit(&env, &client, &whistleblower, 1);
+
+        assert_eq!(listing_id, 1);
+        assert_eq!(env.auths()[0].0, whistleblower);
+        assert_eq!(
+            client.get_listing(&listing_id),
+            Listing {
+                id: 1,
+                whistleblower: whistleblower.clone(),
+                listing_hash: BytesN::from_array(&env, &[1; 32]),
+                status: ListingStatus::PendingReview,
+                submitted_at: 1_000,
+                updated_at: 1_000,
+                rejection_reason: None,
+                deal_id: None,
+            }
+        );
+        assert_eq!(client.listing_count(), 1);
+        assert_eq!(
+            client.get_listing_by_hash(&BytesN::from_array(&env, &[1; 32])).unwrap().id,
+            1
+        );
+    }
+
+    #[test]
+    fn submit_listing_emits_event() {
+        let env = Env::default();
+        let (contract_id, client, _) = setup(&env);
+        let whistleblower = Address::generate(&env);
+
+        submit(&env, &client, &whistleblower, 1);
+
+        let event = env.events().all().last().unwrap();
+        assert_eq!(event.0, contract_id);
+        assert_eq!(
+            event.1,
+            (Symbol::new(&env, "listing_submitted"), 1u64).into_val(&env)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "listing already submitted")]
+    fn submit_listing_rejects_duplicate_hash() {
+        let env = Env::default();
+        let (_, client, _) = setup(&env);
+
+        submit(&env, &client, &Address::generate(&env), 1);
+        submit(&env, &client, &Address::generate(&env), 1);
+    }
+
+    // ============================================================================
+    // Review Tests
+    // ============================================================================
+
+    #[test]
+    fn approve_listing_verifies_it() {
+        let env = Env::default();
+        let (contract_id, client, admin) = setup(&env);
+        let listing_id = submit(&env, &client, &Address::generate(&env), 1);
+
+        env.ledger().set_timestamp(2_000);
+        approve(&env, &client, &contract_id, &admin, listing_id);
+
+        let listing = client.get_listing(&listing_id);
+        assert_eq!(listing.status, ListingStatus::Approved);
+        assert_eq!(listing.updated_at, 2_000);
+    }
+
+    #[test]
+    fn reject_listing_records_reason() {
+        let env = Env::default();
+        let (contract_id, client, admin) = setup(&env);
+        let listing_id = submit(&env, &client, &Address::generate(&env), 1);
+        let reason = Symbol::new(&env, "duplicate");
+
+        env.mock_auths(&[MockAuth {
+            address: &admin,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "reject_listing",
+                args: (listing_id, reason.clone()).into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+        client.reject_listing(&listing_id, &reason);
+
+        let listing = client.get_listing(&listing_id);
+        assert_eq!(listing.status, ListingStatus::Rejected);
+        assert_eq!(listing.rejection_reason, Some(reason));
+    }
+
+    #[test]
+    #[should_panic]
+    fn approve_listing_requires_admin() {
+        let env = Env::default();
+        let (contract_id, client, _) = setup(&env);
+        let whistleblower = Address::generate(&env);
+        let listing_id = submit(&env, &client, &whistleblower, 1);
+
+        approve(&env, &client, &contract_id, &whistleblower, listing_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "listing is not pending review")]
+    fn rejected_listing_cannot_be_approved() {
+        let env = Env::default();
+        let (_, client, _) = setup(&env);
+        let listing_id = submit(&env, &client, &Address::generate(&env), 1);
+
+        client.reject_listing(&listing_id, &Symbol::new(&env, "fake"));
+        client.approve_listing(&listing_id);
+    }
+
+    // ============================================================================
+    // Rented Tests
+    // ============================================================================
+
+    #[test]
+    fn mark_rented_links_deal() {
+        let env = Env::default();
+        let (contract_id, client, admin) = setup(&env);
+        let deals_contract = Address::generate(&env);
+        env.mock_all_auths();
+        client.set_deals_contract(&deals_contract);
+        let listing_id = submit(&env, &client, &Address::generate(&env), 1);
+        approve(&env, &client, &contract_id, &admin, listing_id);
+
+        env.mock_all_auths();
+        client.mark_rented(&listing_id, &7u64);
+        assert_eq!(env.auths()[0].0, deals_contract);
+
+        let listing = client.get_listing(&listing_id);
+        assert_eq!(listing.status, ListingStatus::Rented);
+        assert_eq!(listing.deal_id, Some(7));
+    }
+
+    #[test]
+    #[should_panic(expected = "listing is not approved")]
+    fn mark_rented_requires_approval() {
+        let env = Env::default();
+        let (_, client, _) = setup(&env);
+        env.mock_all_auths();
+        client.set_deals_contract(&Address::generate(&env));
+        let listing_id = submit(&env, &client, &Address::generate(&env), 1);
+
+        client.mark_rented(&listing_id, &7u64);
+    }
+}
@@ -1,9 +1,39 @@
 #![no_std]
 
 use soroban_sdk::{
-    contract, contractimpl, contracttype, vec, Address, Env, Symbol, Vec, BytesN,
+    contract, contractclient, contractimpl, contracttype, token, vec, xdr::ToXdr, Address, Bytes,
+    Env, Map, Symbol, Vec, BytesN,
 };
 
+/// Current on-chain layout version written by `create_receipt` / `record_receipt`
+pub const RECEIPT_VERSION: u32 = 2;
+
+/// Fixed-point scale for `fx_rate` (7 decimal places, matching Stellar assets)
+pub const FX_RATE_SCALE: i128 = 10_000_000;
+
+/// Kobo per naira; NGN amounts are recorded in kobo
+pub const KOBO_PER_NGN: i128 = 100;
+
+/// Spacing between scheduled installments (30 days)
+///
+/// A fixed interval rather than calendar months: due dates drift from the
+/// backend's monthly due dates by about five days a year, so installments
+/// should be matched across the two by `period`, not by date.
+pub const INSTALLMENT_INTERVAL: u64 = 30 * 24 * 60 * 60;
+
+/// Most installments a schedule can be split into (ten years of monthly rent)
+pub const MAX_TERM: u32 = 120;
+
+/// Time after a due date before an unpaid installment counts as late (5 days,
+/// matching the backend's schedule status rules)
+pub const GRACE_PERIOD: u64 = 5 * 24 * 60 * 60;
+
+/// Denominator for basis-point rates such as the early-settlement discount
+pub const BPS_SCALE: i128 = 10_000;
+
+#[cfg(any(test, feature = "proofs"))]
+pub mod proofs;
+
 /// Deal ID type - using u64 for simplicity
 pub type DealId = u64;
 
@@ -25,16 +55,430 @@ pub struct Cursor {
     pub tx_id: TxId, // BytesN<32> is contract-compatible
 }
 
+/// What a receipt records; mirrors the backend's `TxType`
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReceiptKind {
+    Receipt,
+    TenantRepayment,
+    LandlordPayout,
+    WhistleblowerReward,
+    EarlySettlement, // Recorded by `settle_early`; closes the deal
+}
+
+/// Protocol fee charged on a payment, routed to the treasury
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FeeRate {
+    Flat(i128),
+    Bps(u32), // Basis points of the payment amount
+}
+
+impl FeeRate {
+    fn validate(&self) {
+        match self {
+            FeeRate::Flat(amount) if *amount < 0 => panic!("fee must not be negative"),
+            FeeRate::Bps(bps) if *bps as i128 >= BPS_SCALE => {
+                panic!("fee bps must be below 10000")
+            }
+            _ => {}
+        }
+    }
+
+    fn fee_on(&self, amount: i128) -> i128 {
+        match self {
+            FeeRate::Flat(fee) => *fee,
+            FeeRate::Bps(bps) => amount * *bps as i128 / BPS_SCALE,
+        }
+    }
+}
+
+/// Optional metadata supplied to `record_receipt`
+///
+/// Off-chain identifiers (Paystack/Stripe references, invoice payloads) are only
+/// ever stored as SHA-256 hashes so nothing personally identifying lands on-chain.
+/// The NGN fields are set together when a payment was collected in NGN and
+/// settled in tokens.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReceiptDetails {
+    pub external_ref_hash: Option<BytesN<32>>,
+    pub token: Option<Address>,
+    pub amount_ngn: Option<i128>,   // NGN amount in kobo
+    pub fx_rate: Option<i128>,      // NGN per whole token, scaled by FX_RATE_SCALE
+    pub fx_provider: Option<Symbol>, // Rate source, e.g. "paystack"
+    pub from: Option<Address>,
+    pub to: Option<Address>,
+    pub metadata_hash: Option<BytesN<32>>,
+}
+
+/// Receipt layout written before receipt metadata was recorded (version 1)
+///
+/// Kept so receipts stored by earlier deployments can still be decoded; they are
+/// upgraded to `Receipt` on read and rewritten in the current layout the next
+/// time their deal receives a receipt.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReceiptV1 {
+    pub id: ReceiptId,
+    pub deal_id: DealId,
+    pub amount: i128,
+    pub timestamp: Timestamp,
+    pub tx_id: TxId,
+    pub payer: Address,
+}
+
 /// Receipt data structure
+///
+/// `version` is the layout the receipt was originally written with. Receipts
+/// upgraded from version 1 are tenant repayments with every optional field set
+/// to `None`.
 #[contracttype]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Receipt {
+    pub version: u32,
     pub id: ReceiptId,
     pub deal_id: DealId,
+    pub kind: ReceiptKind,
     pub amount: i128,
     pub timestamp: Timestamp,
     pub tx_id: TxId,
     pub payer: Address,
+    pub external_ref_hash: Option<BytesN<32>>, // SHA-256 of the canonical external reference
+    pub token: Option<Address>,                // Token contract the amount is denominated in
+    pub amount_ngn: Option<i128>,
+    pub fx_rate: Option<i128>,
+    pub fx_provider: Option<Symbol>,
+    pub from: Option<Address>,
+    pub to: Option<Address>,
+    pub metadata_hash: Option<BytesN<32>>, // SHA-256 of off-chain receipt metadata
+    pub reverses: Option<ReceiptId>,       // Set on reversal entries: the receipt they undo
+    pub reversed_by: Option<ReceiptId>,    // Set on reversed receipts: their reversal entry
+    pub reversal_reason: Option<Symbol>,   // Set on reversal entries, e.g. "chargeback"
+    pub chain_hash: Option<BytesN<32>>,    // Deal's hash chain head after this receipt
+    pub leaf_index: Option<u64>,           // Position in the all-receipts Merkle accumulator
+    pub allocations: Allocation,           // Where a repayment went; zero for other receipts
+    pub protocol_fee: i128,                // Withheld from `amount` for the treasury
+}
+
+/// How a payment was applied to a deal, in the order set by its
+/// `AllocationPolicy`
+///
+/// `arrears` covers installments past their grace period, `current` the
+/// earliest remaining installment that is not late, and `prepayment` anything
+/// applied to later installments. Reversal entries carry the negated breakdown
+/// of the receipt they undo.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Allocation {
+    pub fee: i128,
+    pub arrears: i128,
+    pub current: i128,
+    pub prepayment: i128,
+}
+
+impl Allocation {
+    pub fn none() -> Self {
+        Allocation {
+            fee: 0,
+            arrears: 0,
+            current: 0,
+            prepayment: 0,
+        }
+    }
+
+    fn negated(&self) -> Self {
+        Allocation {
+            fee: -self.fee,
+            arrears: -self.arrears,
+            current: -self.current,
+            prepayment: -self.prepayment,
+        }
+    }
+}
+
+impl ReceiptDetails {
+    pub fn none() -> Self {
+        ReceiptDetails {
+            external_ref_hash: None,
+            token: None,
+            amount_ngn: None,
+            fx_rate: None,
+            fx_provider: None,
+            from: None,
+            to: None,
+            metadata_hash: None,
+        }
+    }
+}
+
+impl From<ReceiptV1> for Receipt {
+    fn from(r: ReceiptV1) -> Self {
+        Receipt {
+            version: 1,
+            id: r.id,
+            deal_id: r.deal_id,
+            kind: ReceiptKind::TenantRepayment,
+            amount: r.amount,
+            timestamp: r.timestamp,
+            tx_id: r.tx_id,
+            payer: r.payer,
+            external_ref_hash: None,
+            token: None,
+            amount_ngn: None,
+            fx_rate: None,
+            fx_provider: None,
+            from: None,
+            to: None,
+            metadata_hash: None,
+            reverses: None,
+            reversed_by: None,
+            reversal_reason: None,
+            chain_hash: None,
+            leaf_index: None,
+            allocations: Allocation::none(),
+            protocol_fee: 0,
+        }
+    }
+}
+
+/// Lifecycle of a financed deal
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DealStatus {
+    Active,
+    Completed,
+    Defaulted,
+}
+
+/// Order in which a tenant repayment is applied to what the deal owes
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AllocationPolicy {
+    FeesFirst, // Fees, arrears, current installment, prepayment (default)
+    RentFirst, // Arrears, current installment, fees, prepayment
+}
+
+/// A financed deal registered with `create_deal`
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Deal {
+    pub id: DealId,
+    pub tenant: Address,
+    pub landlord: Address,
+    pub principal: i128, // Financed amount repaid through the schedule
+    pub term: u32,       // Number of installments
+    pub status: DealStatus,
+    pub created_at: Timestamp,
+    pub token: Option<Address>,     // Token tenants pay in through `pay`
+    pub recipient: Option<Address>, // Where `pay` sends funds; the treasury when unset
+    pub schedule_version: u32,      // Starts at 1, bumped by every restructure
+    pub allocation_policy: AllocationPolicy,
+    pub listing_id: Option<u64>, // Whistleblower listing in the listings contract
+}
+
+/// One scheduled repayment
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Installment {
+    pub period: u32, // 1-indexed
+    pub due_date: Timestamp,
+    pub amount: i128,
+    pub paid: i128,
+    pub paid_at: Option<Timestamp>, // Set once the installment is fully paid
+}
+
+/// Rate source for a deal denominated in NGN
+///
+/// The deal's principal, schedule and receipt amounts are in kobo. Payments are
+/// converted to the deal's token at the oracle's median rate for `pair` (NGN
+/// per whole token, scaled by `FX_RATE_SCALE`) when they are collected.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DealFx {
+    pub oracle: Address,
+    pub pair: Symbol,     // e.g. "NGN_USDC"
+    pub max_age: u64,     // Oldest report accepted, in seconds
+    pub min_reports: u32, // Fresh reports required for a rate
+}
+
+/// Standing authorization from a tenant to collect due installments
+///
+/// The tenant also approves this contract as a spender of the deal's token;
+/// `execute_due` then pulls each installment with `transfer_from`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mandate {
+    pub deal_id: DealId,
+    pub payer: Address,
+    pub token: Address,     // Token the tenant agreed to be charged in
+    pub recipient: Address, // Where the tenant agreed collections go
+    pub max_per_installment: i128,
+    pub valid_until: Timestamp,
+    pub created_at: Timestamp,
+}
+
+/// Running totals maintained by every receipt for a deal
+///
+/// Amounts are receipt amounts, i.e. in the payment token (see `Receipt.amount`).
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DealTotals {
+    pub total_paid: i128, // Sum of tenant repayments and early settlements
+    pub receipt_count: u64,
+    pub last_payment_at: Option<Timestamp>, // Timestamp of the latest tenant repayment
+    pub by_kind: Map<ReceiptKind, i128>,
+}
+
+/// Payment position of a deal, returned by `deal_summary`
+///
+/// For deals that were never registered with `create_deal`, `registered` is
+/// false, `status` is reported as `Active` and the schedule fields are zero/`None`.
+/// `total_paid` and `by_kind` are in the payment token; `outstanding`,
+/// `next_due_amount` and `fees_due` are in the deal's units (kobo for
+/// NGN-denominated deals).
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DealSummary {
+    pub deal_id: DealId,
+    pub registered: bool,
+    pub status: DealStatus,
+    pub total_paid: i128,
+    pub receipt_count: u64,
+    pub last_payment_at: Option<Timestamp>,
+    pub by_kind: Map<ReceiptKind, i128>,
+    pub outstanding: i128,
+    pub next_due_date: Option<Timestamp>,
+    pub next_due_amount: i128,
+    pub installments_paid: u32,
+    pub installments_late: u32,
+    pub fees_due: i128,
+}
+
+/// Amount that closes a deal today, returned by `payoff_quote`
+///
+/// `amount = outstanding + fees - discount`, where the discount is the deal's
+/// early-settlement rate applied to the outstanding principal only.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PayoffQuote {
+    pub deal_id: DealId,
+    pub outstanding: i128,
+    pub fees: i128,
+    pub discount: i128,
+    pub amount: i128,
+}
+
+/// A tenant's repayment history across all their deals, kept by `tenant_record`
+///
+/// An installment counts as on time if it was fully paid within its grace
+/// period. Reversing a repayment takes back the counts it earned.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TenantRecord {
+    pub installments_on_time: u32,
+    pub installments_late: u32,
+    pub installments_defaulted: u32, // Unpaid when their deal was marked defaulted
+    pub deals_completed: u32,
+    pub deals_defaulted: u32,
+    pub total_repaid: i128, // Repayments and settlements in tokens, net of reversals
+}
+
+/// What a financier needs to know about a deal, returned by `deal_position`
+///
+/// Amounts are in the deal's token; NGN-denominated deals are valued at the
+/// oracle's current rate.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DealPosition {
+    pub status: DealStatus,
+    pub landlord: Address,
+    pub principal: i128,
+    pub outstanding: i128, // Unpaid principal left on the schedule
+}
+
+/// A fee charged to a deal with `assess_fee`
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FeeEntry {
+    pub amount: i128,
+    pub reason: Symbol,
+    pub timestamp: Timestamp,
+}
+
+/// A deal's account over `[period_start, period_end]`, returned by `statement`
+///
+/// Balances are what the tenant owes (unpaid principal plus fees), replayed
+/// from the deal's fee log and receipts, and always satisfy
+/// `closing_balance = opening_balance + advanced + fees_accrued - payments - discounts`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Statement {
+    pub deal_id: DealId,
+    pub period_start: Timestamp,
+    pub period_end: Timestamp,
+    pub opening_balance: i128,
+    pub advanced: i128,              // Principal, if the deal was created in the period
+    pub fees_accrued: i128,
+    pub fees: Vec<FeeEntry>,         // Fees assessed in the period
+    pub payments: i128,              // Net of reversals
+    pub discounts: i128,             // Early-settlement discounts
+    pub allocations: Allocation,     // Sum of the period's receipt allocations
+    pub receipts: Vec<Receipt>,      // Every receipt in the period, in id order
+    pub closing_balance: i128,
+}
+
+/// Location of a receipt, stored by the reverse indexes
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReceiptRef {
+    pub deal_id: DealId,
+    pub receipt_id: ReceiptId,
+}
+
+/// Filters and ordering for `query_receipts_by_deal` / `query_receipts_by_payer`
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReceiptQuery {
+    pub from_timestamp: Option<Timestamp>, // Inclusive lower bound
+    pub to_timestamp: Option<Timestamp>,   // Inclusive upper bound
+    pub descending: bool,                  // Newest first when true
+}
+
+impl ReceiptQuery {
+    /// All receipts, oldest first (the `list_receipts_by_*` behaviour)
+    pub fn all() -> Self {
+        ReceiptQuery {
+            from_timestamp: None,
+            to_timestamp: None,
+            descending: false,
+        }
+    }
+}
+
+/// Inclusion proof for a receipt in the all-receipts Merkle accumulator
+///
+/// `siblings` are the hashes from the receipt's leaf up to its mountain peak
+/// (lowest first); `peaks` and `leaf_count` describe the accumulator the proof
+/// was built against, the current one or any earlier state.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReceiptProof {
+    pub leaf_count: u64,
+    pub siblings: Vec<BytesN<32>>,
+    pub peaks: Vec<BytesN<32>>,
+}
+
+/// Entry in a payer's receipt index
+///
+/// Carries the receipt's sort key so pages can be located without loading
+/// receipts.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PayerIndexEntry {
+    pub timestamp: Timestamp,
+    pub tx_id: TxId,
+    pub receipt: ReceiptRef,
 }
 
 /// Paginated result for list_receipts_by_deal
@@ -44,6 +488,8 @@ pub struct ReceiptPage {
     pub receipts: Vec<Receipt>,
     pub has_next: bool, // True if there are more receipts
     pub next_cursor: Cursor, // Cursor for next page (only valid if has_next is true)
+    pub has_prev: bool, // True if receipts precede this page
+    pub prev_cursor: Cursor, // Cursor for previous page (only valid if has_prev is true)
 }
 
 #[contracttype]
@@ -51,8 +497,48 @@ pub struct ReceiptPage {
 pub enum DataKey {
     Admin,
     Deals,
-    Receipts(DealId),
+    Receipts(DealId),   // Version 1 receipts (Vec<ReceiptV1>), read-only
+    ReceiptsV2(DealId),
     ReceiptCount(DealId),
+    ReceiptByTxId(TxId),
+    ReceiptByExternalRef(BytesN<32>),
+    PayerReceiptCount(Address),
+    PayerReceipt(Address, u64), // PayerIndexEntry, in (timestamp, tx_id) order
+    Deal(DealId),
+    Schedule(DealId),
+    DealTotals(DealId),
+    ChainHead(DealId),
+    MmrPeaks,
+    MmrLeafCount,
+    MmrRoot(u64), // Root once the accumulator held this many leaves
+    Treasury,
+    Mandate(DealId),
+    FeesDue(DealId),
+    PriorSchedule(DealId, u32), // Schedule replaced by a restructure, by version
+    FeeLog(DealId),
+    TenantDeals(Address),
+    TenantRecord(Address),
+    ListingsContract,
+    Reserve,
+    ReserveCutBps,
+    TotalOutstanding, // Unpaid principal across active deals
+    ProtocolFee(ReceiptKind),
+    FeesCollected, // Protocol fees paid to the treasury, by token
+    DealFx(DealId),
+    SettlementDiscount(DealId), // Basis points off the outstanding principal
+    ReceiptInstallments(DealId, ReceiptId), // Map<u32, i128>: what a repayment paid, by period
+}
+
+/// The part of the listings contract that deals call into
+#[contractclient(name = "ListingsClient")]
+pub trait ListingsInterface {
+    fn mark_rented(env: Env, listing_id: u64, deal_id: DealId);
+}
+
+/// The part of the FX oracle that NGN-denominated deals read
+#[contractclient(name = "FxOracleClient")]
+pub trait FxOracleInterface {
+    fn median(env: Env, pair: Symbol, max_age: u64, min_reports: u32) -> i128;
 }
 
 #[contract]
@@ -70,17 +556,375 @@ fn require_admin(env: &Env) {
     admin.require_auth();
 }
 
-fn get_receipts(env: &Env, deal_id: DealId) -> Vec<Receipt> {
+fn get_treasury(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&DataKey::Treasury)
+}
+
+/// Token and destination for payments the contract moves on a deal's behalf
+fn payment_route(env: &Env, deal: &Deal) -> (Address, Address) {
+    let token = deal.token.clone().expect("deal has no payment token");
+    let recipient = deal
+        .recipient
+        .clone()
+        .or_else(|| get_treasury(env))
+        .expect("no payment recipient configured");
+    (token, recipient)
+}
+
+/// Move a payment in the deal's token from `payer` and record it
+///
+/// For NGN-denominated deals `amount` is in kobo and is converted to the token
+/// at the oracle's rate; the receipt records the token amount, with the kobo in
+/// `amount_ngn`. With `pull`, the tokens are collected under the payer's
+/// allowance to this contract (mandates) instead of their own authorization.
+/// The protocol fee for `kind` goes to the treasury and, if a reserve is
+/// configured, its cut to the reserve; the rest goes to the deal's recipient.
+fn collect_payment(
+    env: &Env,
+    deal: &Deal,
+    kind: ReceiptKind,
+    payer: Address,
+    amount: i128,
+    pull: bool,
+) -> Receipt {
+    let (token, recipient) = payment_route(env, deal);
+    let (paid, fx_rate) = to_token_amount(env, deal.id, &token, amount);
+
+    let client = token::Client::new(env, &token);
+    let send = |to: &Address, value: i128| {
+        if pull {
+            client.transfer_from(&env.current_contract_address(), &payer, to, &value);
+        } else {
+            client.transfer(&payer, to, &value);
+        }
+    };
+
+    let reserve: Option<Address> = env.storage().instance().get(&DataKey::Reserve);
+    let cut_bps: u32 = env
+        .storage()
+        .instance()
+        .get(&DataKey::ReserveCutBps)
+        .unwrap_or(0);
+    let cut = paid * cut_bps as i128 / BPS_SCALE;
+    let fee = get_protocol_fee(env, kind).map_or(0, |rate| rate.fee_on(paid));
+    if cut + fee > paid {
+        panic!("fees exceed payment amount");
+    }
+    if let (Some(reserve), true) = (&reserve, cut > 0) {
+        send(reserve, cut);
+    }
+    let treasury = get_treasury(env);
+    if fee > 0 {
+        send(treasury.as_ref().expect("no treasury configured"), fee);
+        let mut collected = get_fees_collected(env);
+        collected.set(token.clone(), collected.get(token.clone()).unwrap_or(0) + fee);
+        env.storage().instance().set(&DataKey::FeesCollected, &collected);
+    }
+    send(&recipient, paid - cut - fee);
+
+    let details = ReceiptDetails {
+        token: Some(token),
+        amount_ngn: fx_rate.map(|_| amount),
+        fx_rate,
+        fx_provider: fx_rate.map(|_| Symbol::new(env, "fx_oracle")),
+        from: Some(payer.clone()),
+        to: Some(recipient),
+        ..ReceiptDetails::none()
+    };
+    let receipt = store_receipt(env, deal.id, kind, paid, payer, details, fee, true);
+    if let (Some(reserve), true) = (reserve, cut > 0) {
+        env.events().publish(
+            (Symbol::new(env, "reserve_cut"), deal.id),
+            (receipt.id, reserve, cut),
+        );
+    }
+    if let (Some(treasury), true) = (treasury, fee > 0) {
+        env.events().publish(
+            (Symbol::new(env, "protocol_fee"), deal.id),
+            (receipt.id, treasury, fee),
+        );
+    }
+    receipt
+}
+
+/// Token amount `amount` (in the deal's units) transfers now, with the rate used
+/// for NGN-denominated deals
+fn to_token_amount(env: &Env, deal_id: DealId, token: &Address, amount: i128) -> (i128, Option<i128>) {
+    match get_deal_fx(env, deal_id) {
+        Some(fx) => {
+            let (paid, rate) = convert_ngn(env, &fx, token, amount);
+            (paid, Some(rate))
+        }
+        None => (amount, None),
+    }
+}
+
+/// Token amount `amount` (in the deal's units) is worth now
+fn quote_payment(env: &Env, deal: &Deal, amount: i128) -> i128 {
+    match get_deal_fx(env, deal.id) {
+        Some(fx) => {
+            let token = deal.token.clone().expect("deal has no payment token");
+            convert_ngn(env, &fx, &token, amount).0
+        }
+        None => amount,
+    }
+}
+
+fn get_protocol_fee(env: &Env, kind: ReceiptKind) -> Option<FeeRate> {
+    env.storage().instance().get(&DataKey::ProtocolFee(kind))
+}
+
+fn get_fees_collected(env: &Env) -> Map<Address, i128> {
     env.storage()
+        .instance()
+        .get(&DataKey::FeesCollected)
+        .unwrap_or_else(|| Map::new(env))
+}
+
+fn get_deal_fx(env: &Env, deal_id: DealId) -> Option<DealFx> {
+    env.storage().persistent().get(&DataKey::DealFx(deal_id))
+}
+
+/// Token amount for `amount_ngn` kobo at the oracle's current rate, rounded
+/// up so the recipient is never short; returns the amount and the rate used
+fn convert_ngn(env: &Env, fx: &DealFx, token: &Address, amount_ngn: i128) -> (i128, i128) {
+    let rate =
+        FxOracleClient::new(env, &fx.oracle).median(&fx.pair, &fx.max_age, &fx.min_reports);
+    if rate <= 0 {
+        panic!("fx rate must be positive");
+    }
+    let unit = 10i128.pow(token::Client::new(env, token).decimals());
+    let numerator = amount_ngn * unit * FX_RATE_SCALE;
+    let denominator = KOBO_PER_NGN * rate;
+    ((numerator + denominator - 1) / denominator, rate)
+}
+
+fn get_mandate(env: &Env, deal_id: DealId) -> Option<Mandate> {
+    env.storage().persistent().get(&DataKey::Mandate(deal_id))
+}
+
+fn get_receipts(env: &Env, deal_id: DealId) -> Vec<Receipt> {
+    if let Some(receipts) = env
+        .storage()
         .persistent()
-        .get::<_, Vec<Receipt>>(&DataKey::Receipts(deal_id))
-        .unwrap_or_else(|| vec![env])
+        .get::<_, Vec<Receipt>>(&DataKey::ReceiptsV2(deal_id))
+    {
+        return receipts;
+    }
+
+    // Fall back to receipts written in the version 1 layout
+    let mut receipts = vec![env];
+    if let Some(legacy) = env
+        .storage()
+        .persistent()
+        .get::<_, Vec<ReceiptV1>>(&DataKey::Receipts(deal_id))
+    {
+        for r in legacy.iter() {
+            receipts.push_back(Receipt::from(r));
+        }
+    }
+    receipts
 }
 
 fn put_receipts(env: &Env, deal_id: DealId, receipts: Vec<Receipt>) {
+    let storage = env.storage().persistent();
+    storage.set(&DataKey::ReceiptsV2(deal_id), &receipts);
+    // Any version 1 receipts were carried over by get_receipts; they predate
+    // the reverse indexes, so index them as they move to the current layout
+    if storage.has(&DataKey::Receipts(deal_id)) {
+        storage.remove(&DataKey::Receipts(deal_id));
+        for r in receipts.iter().filter(|r| r.version == 1) {
+            index_receipt(env, &r);
+        }
+    }
+}
+
+/// Look up a single receipt; ids are assigned sequentially per deal from 1
+fn get_receipt(env: &Env, receipt_ref: &ReceiptRef) -> Option<Receipt> {
+    if receipt_ref.receipt_id == 0 {
+        return None;
+    }
+    get_receipts(env, receipt_ref.deal_id).get((receipt_ref.receipt_id - 1) as u32)
+}
+
+fn index_receipt(env: &Env, receipt: &Receipt) {
+    let receipt_ref = ReceiptRef {
+        deal_id: receipt.deal_id,
+        receipt_id: receipt.id,
+    };
+    let storage = env.storage().persistent();
+    storage.set(&DataKey::ReceiptByTxId(receipt.tx_id.clone()), &receipt_ref);
+    if let Some(hash) = &receipt.external_ref_hash {
+        storage.set(&DataKey::ReceiptByExternalRef(hash.clone()), &receipt_ref);
+    }
+
+    insert_payer_entry(env, &receipt.payer, payer_entry_of(receipt));
+}
+
+fn payer_entry_of(receipt: &Receipt) -> PayerIndexEntry {
+    PayerIndexEntry {
+        timestamp: receipt.timestamp,
+        tx_id: receipt.tx_id.clone(),
+        receipt: ReceiptRef {
+            deal_id: receipt.deal_id,
+            receipt_id: receipt.id,
+        },
+    }
+}
+
+fn get_payer_count(env: &Env, payer: &Address) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::PayerReceiptCount(payer.clone()))
+        .unwrap_or(0)
+}
+
+fn get_payer_entry(env: &Env, payer: &Address, index: u64) -> PayerIndexEntry {
     env.storage()
         .persistent()
-        .set(&DataKey::Receipts(deal_id), &receipts);
+        .get(&DataKey::PayerReceipt(payer.clone(), index))
+        .expect("payer index entry missing")
+}
+
+/// Order a payer index entry relative to a cursor position by (timestamp, tx_id)
+fn cmp_entry_to_cursor(entry: &PayerIndexEntry, cursor: &Cursor) -> core::cmp::Ordering {
+    match entry.timestamp.cmp(&cursor.timestamp) {
+        core::cmp::Ordering::Equal => entry.tx_id.to_array().cmp(&cursor.tx_id.to_array()),
+        ordering => ordering,
+    }
+}
+
+/// Append to a payer's index, keeping it in (timestamp, tx_id) order
+///
+/// Receipts are written at the ledger time, so a new entry only moves past
+/// entries from the same timestamp with a larger `tx_id`, or version 1 receipts
+/// indexed as they are upgraded.
+fn insert_payer_entry(env: &Env, payer: &Address, entry: PayerIndexEntry) {
+    let storage = env.storage().persistent();
+    let key = Cursor {
+        timestamp: entry.timestamp,
+        tx_id: entry.tx_id.clone(),
+    };
+    let mut index = get_payer_count(env, payer);
+    storage.set(&DataKey::PayerReceiptCount(payer.clone()), &(index + 1));
+    while index > 0 {
+        let prev = get_payer_entry(env, payer, index - 1);
+        if cmp_entry_to_cursor(&prev, &key) != core::cmp::Ordering::Greater {
+            break;
+        }
+        storage.set(&DataKey::PayerReceipt(payer.clone(), index), &prev);
+        index -= 1;
+    }
+    storage.set(&DataKey::PayerReceipt(payer.clone(), index), &entry);
+}
+
+/// First index in `lo..hi` of the payer's index where `before` is false;
+/// `before` must hold for a prefix of the range
+fn payer_partition(
+    env: &Env,
+    payer: &Address,
+    mut lo: u64,
+    mut hi: u64,
+    before: impl Fn(&PayerIndexEntry) -> bool,
+) -> u64 {
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if before(&get_payer_entry(env, payer, mid)) {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+/// Page through a payer's index with the semantics of `paginate_receipts`,
+/// loading only the receipts on the page
+fn paginate_payer_receipts(
+    env: &Env,
+    payer: &Address,
+    limit: u32,
+    cursor: Option<Cursor>,
+    query: &ReceiptQuery,
+) -> ReceiptPage {
+    validate_page_request(limit, query);
+
+    // Entries inside the requested time range occupy lo..hi
+    let count = get_payer_count(env, payer);
+    let lo = match query.from_timestamp {
+        Some(from) => payer_partition(env, payer, 0, count, |e| e.timestamp < from),
+        None => 0,
+    };
+    let hi = match query.to_timestamp {
+        Some(to) => payer_partition(env, payer, lo, count, |e| e.timestamp <= to),
+        None => count,
+    };
+
+    // The page covers start..end of the index, walked backwards when descending
+    let (start, end, has_prev, has_next) = if query.descending {
+        let top = match &cursor {
+            Some(c) => payer_partition(env, payer, lo, hi, |e| {
+                cmp_entry_to_cursor(e, c) == core::cmp::Ordering::Less
+            }),
+            None => hi,
+        };
+        let bottom = top.saturating_sub(limit as u64).max(lo);
+        (bottom, top, top < hi, bottom > lo)
+    } else {
+        let start = match &cursor {
+            Some(c) => payer_partition(env, payer, lo, hi, |e| {
+                cmp_entry_to_cursor(e, c) != core::cmp::Ordering::Greater
+            }),
+            None => lo,
+        };
+        let end = start.saturating_add(limit as u64).min(hi);
+        (start, end, start > lo, end < hi)
+    };
+
+    // Load each deal's receipts once, however many of them the page holds
+    let mut by_deal: Map<DealId, Vec<Receipt>> = Map::new(env);
+    let mut page_receipts = vec![env];
+    for i in start..end {
+        let receipt_ref = get_payer_entry(env, payer, i).receipt;
+        let deal_receipts = match by_deal.get(receipt_ref.deal_id) {
+            Some(deal_receipts) => deal_receipts,
+            None => {
+                let deal_receipts = get_receipts(env, receipt_ref.deal_id);
+                by_deal.set(receipt_ref.deal_id, deal_receipts.clone());
+                deal_receipts
+            }
+        };
+        let receipt = deal_receipts
+            .get((receipt_ref.receipt_id - 1) as u32)
+            .expect("indexed receipt missing");
+        if query.descending {
+            page_receipts.push_front(receipt);
+        } else {
+            page_receipts.push_back(receipt);
+        }
+    }
+
+    // Cursors are only meaningful when the matching has_* flag is set
+    let empty_cursor = Cursor {
+        timestamp: 0,
+        tx_id: BytesN::from_array(env, &[0u8; 32]),
+    };
+    let next_cursor = match page_receipts.last() {
+        Some(last) if has_next => cursor_of(&last),
+        _ => empty_cursor.clone(),
+    };
+    let prev_cursor = match page_receipts.first() {
+        Some(first) if has_prev => cursor_of(&first),
+        _ => empty_cursor,
+    };
+
+    ReceiptPage {
+        receipts: page_receipts,
+        has_next,
+        next_cursor,
+        has_prev,
+        prev_cursor,
+    }
 }
 
 fn get_receipt_count(env: &Env, deal_id: DealId) -> ReceiptId {
@@ -99,6 +943,7 @@ fn increment_receipt_count(env: &Env, deal_id: DealId) -> ReceiptId {
     new_count
 }
 
+#[allow(clippy::needless_range_loop)]
 fn get_tx_id(env: &Env) -> TxId {
     // In Soroban, we can use the ledger timestamp and sequence to create a unique ID
     // For production, you might want to use the actual transaction hash
@@ -144,507 +989,4609 @@ fn get_tx_id(env: &Env) -> TxId {
     BytesN::from_array(env, &bytes)
 }
 
-#[contractimpl]
-impl RentPayments {
-    pub fn init(env: Env, admin: Address) {
-        if env.storage().instance().has(&DataKey::Admin) {
-            panic!("already initialized");
-        }
-        env.storage().instance().set(&DataKey::Admin, &admin);
-        env.events().publish((Symbol::new(&env, "init"),), admin);
+fn get_deal(env: &Env, deal_id: DealId) -> Option<Deal> {
+    env.storage().persistent().get(&DataKey::Deal(deal_id))
+}
+
+fn put_deal(env: &Env, deal: &Deal) {
+    env.storage().persistent().set(&DataKey::Deal(deal.id), deal);
+}
+
+fn get_schedule(env: &Env, deal_id: DealId) -> Vec<Installment> {
+    env.storage()
+        .persistent()
+        .get::<_, Vec<Installment>>(&DataKey::Schedule(deal_id))
+        .unwrap_or_else(|| vec![env])
+}
+
+/// Store a deal's schedule; `TotalOutstanding` follows the change while the
+/// deal counts towards it (see `in_total_outstanding`)
+fn put_schedule(env: &Env, deal: &Deal, schedule: &Vec<Installment>) {
+    if in_total_outstanding(env, deal) {
+        let change = outstanding_of(schedule) - outstanding_of(&get_schedule(env, deal.id));
+        adjust_total_outstanding(env, change);
     }
+    env.storage()
+        .persistent()
+        .set(&DataKey::Schedule(deal.id), schedule);
+}
 
-    /// Create a new receipt for a deal
-    /// This function records a monthly payment receipt
-    pub fn create_receipt(
-        env: Env,
-        deal_id: DealId,
-        amount: i128,
-        payer: Address,
-    ) -> Receipt {
-        require_admin(&env);
-        
-        if amount <= 0 {
-            panic!("amount must be positive");
-        }
+fn get_total_outstanding(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKey::TotalOutstanding)
+        .unwrap_or(0)
+}
 
-        let receipt_id = increment_receipt_count(&env, deal_id);
-        let timestamp = env.ledger().timestamp();
-        let tx_id = get_tx_id(&env);
-        let payer_clone = payer.clone();
+/// `TotalOutstanding` is in tokens, so it leaves out NGN-denominated deals
+/// along with defaulted ones, which `mark_default` already took out of it
+fn in_total_outstanding(env: &Env, deal: &Deal) -> bool {
+    deal.status != DealStatus::Defaulted && get_deal_fx(env, deal.id).is_none()
+}
 
-        let receipt = Receipt {
-            id: receipt_id,
-            deal_id,
-            amount,
-            timestamp,
-            tx_id: tx_id.clone(),
-            payer: payer_clone.clone(),
+fn adjust_total_outstanding(env: &Env, change: i128) {
+    if change != 0 {
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalOutstanding, &(get_total_outstanding(env) + change));
+    }
+}
+
+/// Split `principal` into `term` equal installments, the last one absorbing
+/// any rounding remainder (same strategy as the backend schedule generator)
+fn build_schedule(env: &Env, principal: i128, term: u32, first_due: Timestamp) -> Vec<Installment> {
+    let base = principal / term as i128;
+    let mut schedule = vec![env];
+    for i in 0..term {
+        let amount = if i + 1 == term {
+            principal - base * (term as i128 - 1)
+        } else {
+            base
         };
+        let due_date = (i as u64)
+            .checked_mul(INSTALLMENT_INTERVAL)
+            .and_then(|offset| first_due.checked_add(offset))
+            .expect("due date out of range");
+        schedule.push_back(Installment {
+            period: i + 1,
+            due_date,
+            amount,
+            paid: 0,
+            paid_at: None,
+        });
+    }
+    schedule
+}
 
-        let mut receipts = get_receipts(&env, deal_id);
-        receipts.push_back(receipt.clone());
-        put_receipts(&env, deal_id, receipts);
+fn outstanding_of(schedule: &Vec<Installment>) -> i128 {
+    schedule.iter().map(|i| i.amount - i.paid).sum()
+}
 
-        env.events().publish(
-            (Symbol::new(&env, "receipt_created"), deal_id),
-            (receipt_id, amount, payer_clone),
-        );
+fn is_late(installment: &Installment, now: Timestamp) -> bool {
+    installment.paid < installment.amount && now > installment.due_date + GRACE_PERIOD
+}
 
-        receipt
+/// Pay down installments the `include` filter accepts, oldest first
+fn pay_installments(
+    schedule: &mut Vec<Installment>,
+    remaining: &mut i128,
+    timestamp: Timestamp,
+    include: impl Fn(&Installment) -> bool,
+) -> i128 {
+    let mut total = 0;
+    for i in 0..schedule.len() {
+        if *remaining == 0 {
+            break;
+        }
+        let mut installment = schedule.get(i).unwrap();
+        let due = installment.amount - installment.paid;
+        if due == 0 || !include(&installment) {
+            continue;
+        }
+        let applied = if *remaining < due { *remaining } else { due };
+        installment.paid += applied;
+        if installment.paid == installment.amount {
+            installment.paid_at = Some(timestamp);
+        }
+        *remaining -= applied;
+        total += applied;
+        schedule.set(i, installment);
     }
+    total
+}
 
-    /// List receipts for a deal with cursor-based pagination
-    /// 
-    /// # Arguments
-    /// * `deal_id` - The deal ID to list receipts for
-    /// * `limit` - Maximum number of receipts to return (must be > 0 and <= 100)
-    /// * `cursor` - Optional cursor for pagination. If None, starts from the beginning.
-    ///              Cursor format: (timestamp, tx_id) tuple
-    /// 
-    /// # Returns
-    /// * `ReceiptPage` containing:
-    ///   - `receipts`: Vec of receipts ordered by (timestamp ASC, tx_id ASC)
-    ///   - `next_cursor`: Optional cursor for the next page, None if this is the last page
-    /// 
-    /// # Ordering
-    /// Receipts are ordered by:
-    /// 1. `timestamp` (ascending)
-    /// 2. `tx_id` (ascending, as bytes comparison)
-    /// 
-    /// This ensures stable, deterministic ordering even if multiple receipts have the same timestamp.
-    pub fn list_receipts_by_deal(
-        env: Env,
-        deal_id: DealId,
-        limit: u32,
-        cursor: Option<Cursor>,
-    ) -> ReceiptPage {
-        if limit == 0 || limit > 100 {
-            panic!("limit must be between 1 and 100");
+fn pay_fees(fees: &mut i128, remaining: &mut i128) -> i128 {
+    let applied = if *remaining < *fees { *remaining } else { *fees };
+    *fees -= applied;
+    *remaining -= applied;
+    applied
+}
+
+/// Split `amount` across fees and installments in `policy` order
+fn allocate(
+    schedule: &mut Vec<Installment>,
+    fees: &mut i128,
+    amount: i128,
+    timestamp: Timestamp,
+    policy: AllocationPolicy,
+) -> Allocation {
+    if amount > outstanding_of(schedule) + *fees {
+        panic!("amount exceeds outstanding balance");
+    }
+
+    let current_period = schedule
+        .iter()
+        .find(|i| i.paid < i.amount && !is_late(i, timestamp))
+        .map(|i| i.period);
+    let mut remaining = amount;
+    let mut allocation = Allocation::none();
+
+    if policy == AllocationPolicy::FeesFirst {
+        allocation.fee = pay_fees(fees, &mut remaining);
+    }
+    allocation.arrears = pay_installments(schedule, &mut remaining, timestamp, |i| is_late(i, timestamp));
+    allocation.current =
+        pay_installments(schedule, &mut remaining, timestamp, |i| Some(i.period) == current_period);
+    if policy == AllocationPolicy::RentFirst {
+        allocation.fee = pay_fees(fees, &mut remaining);
+    }
+    allocation.prepayment = pay_installments(schedule, &mut remaining, timestamp, |_| true);
+    allocation
+}
+
+fn get_tenant_record(env: &Env, tenant: &Address) -> TenantRecord {
+    env.storage()
+        .persistent()
+        .get(&DataKey::TenantRecord(tenant.clone()))
+        .unwrap_or(TenantRecord {
+            installments_on_time: 0,
+            installments_late: 0,
+            installments_defaulted: 0,
+            deals_completed: 0,
+            deals_defaulted: 0,
+            total_repaid: 0,
+        })
+}
+
+fn update_tenant_record(env: &Env, tenant: &Address, update: impl FnOnce(&mut TenantRecord)) {
+    let mut record = get_tenant_record(env, tenant);
+    update(&mut record);
+    env.storage()
+        .persistent()
+        .set(&DataKey::TenantRecord(tenant.clone()), &record);
+}
+
+/// Count installments a schedule change paid off (or reopened) as on time or late
+fn track_installments(env: &Env, tenant: &Address, before: &Vec<Installment>, after: &Vec<Installment>) {
+    update_tenant_record(env, tenant, |record| {
+        for (old, new) in before.iter().zip(after.iter()) {
+            let (paid_at, delta) = match (old.paid_at, new.paid_at) {
+                (None, Some(paid_at)) => (paid_at, 1),
+                (Some(paid_at), None) => (paid_at, -1),
+                _ => continue,
+            };
+            let counter = if paid_at <= new.due_date + GRACE_PERIOD {
+                &mut record.installments_on_time
+            } else {
+                &mut record.installments_late
+            };
+            *counter = counter.saturating_add_signed(delta);
         }
+    });
+}
 
-        let receipts = get_receipts(&env, deal_id);
-        let receipts_len = receipts.len();
+/// Apply a tenant repayment to the deal's fees and installments; `repaid` is
+/// its token value, added to the tenant's `total_repaid`
+fn apply_to_schedule(
+    env: &Env,
+    deal: &mut Deal,
+    amount: i128,
+    repaid: i128,
+    timestamp: Timestamp,
+) -> Allocation {
+    let before = get_schedule(env, deal.id);
+    let mut schedule = before.clone();
+    let mut fees = get_fees_due(env, deal.id);
+    let allocation = allocate(&mut schedule, &mut fees, amount, timestamp, deal.allocation_policy);
+    put_schedule(env, deal, &schedule);
+    put_fees_due(env, deal.id, fees);
+    track_installments(env, &deal.tenant, &before, &schedule);
 
-        // Convert to a sortable format - we'll need to collect into a Vec for sorting
-        // Since Soroban Vec doesn't support sort_by directly, we'll build a sorted Vec
-        let mut sorted_receipts = vec![&env];
-        
-        // Collect all receipts into the sorted Vec
-        for i in 0..receipts_len {
-            sorted_receipts.push_back(receipts.get(i).unwrap());
-        }
-
-        // Sort receipts by (timestamp, tx_id) in ascending order
-        // We'll use a simple bubble sort since we can't use sort_by in no_std easily
-        // For production, consider using a more efficient algorithm or storing sorted
-        if sorted_receipts.len() > 1 {
-            let mut swapped = true;
-            while swapped {
-                swapped = false;
-                let len = sorted_receipts.len();
-                for i in 0..(len - 1) {
-                    let a = sorted_receipts.get(i).unwrap();
-                    let b = sorted_receipts.get(i + 1).unwrap();
-                    
-                    let should_swap = match a.timestamp.cmp(&b.timestamp) {
-                        core::cmp::Ordering::Greater => true,
-                        core::cmp::Ordering::Equal => {
-                            a.tx_id.to_array() > b.tx_id.to_array()
-                        }
-                        core::cmp::Ordering::Less => false,
-                    };
-                    
-                    if should_swap {
-                        sorted_receipts.set(i, b.clone());
-                        sorted_receipts.set(i + 1, a.clone());
-                        swapped = true;
-                    }
-                }
-            }
+    let completed = outstanding_of(&schedule) == 0 && fees == 0;
+    update_tenant_record(env, &deal.tenant, |record| {
+        record.total_repaid += repaid;
+        if completed {
+            record.deals_completed += 1;
         }
+    });
+    if completed {
+        deal.status = DealStatus::Completed;
+        put_deal(env, deal);
+        env.events()
+            .publish((Symbol::new(env, "deal_completed"), deal.id), timestamp);
+    }
+    allocation
+}
 
-        // Find the starting point based on cursor
-        let mut start_index = 0u32;
-        if let Some(cursor) = cursor {
-            let cursor_tx_id_array = cursor.tx_id.to_array();
-            
-            // Find the first receipt with (timestamp, tx_id) > cursor
-            for i in 0..sorted_receipts.len() {
-                let r = sorted_receipts.get(i).unwrap();
-                let r_tx_id_array = r.tx_id.to_array();
-                if r.timestamp > cursor.timestamp
-                    || (r.timestamp == cursor.timestamp
-                        && r_tx_id_array > cursor_tx_id_array)
-                {
-                    start_index = i;
-                    break;
-                }
-            }
-            // If no receipt found, start_index remains at end
-            if start_index == 0 && sorted_receipts.len() > 0 {
-                let first = sorted_receipts.get(0).unwrap();
-                let first_tx_id_array = first.tx_id.to_array();
-                if !(first.timestamp > cursor.timestamp
-                    || (first.timestamp == cursor.timestamp
-                        && first_tx_id_array > cursor_tx_id_array))
-                {
-                    start_index = sorted_receipts.len();
-                }
-            }
+/// Close a deal with an early settlement of `amount` (its payoff quote)
+///
+/// The amount is broken down fees first; the settlement discount comes off the
+/// latest installments, which are marked paid along with the rest. `repaid` is
+/// as for `apply_to_schedule`.
+fn settle_schedule(
+    env: &Env,
+    deal: &mut Deal,
+    amount: i128,
+    repaid: i128,
+    timestamp: Timestamp,
+) -> Allocation {
+    let before = get_schedule(env, deal.id);
+    let mut schedule = before.clone();
+    let mut fees = get_fees_due(env, deal.id);
+    let allocation = allocate(&mut schedule, &mut fees, amount, timestamp, AllocationPolicy::FeesFirst);
+    for i in 0..schedule.len() {
+        let mut installment = schedule.get(i).unwrap();
+        if installment.paid < installment.amount {
+            installment.paid = installment.amount;
+            installment.paid_at = Some(timestamp);
+            schedule.set(i, installment);
+        }
+    }
+    put_schedule(env, deal, &schedule);
+    put_fees_due(env, deal.id, 0);
+    track_installments(env, &deal.tenant, &before, &schedule);
+    update_tenant_record(env, &deal.tenant, |record| {
+        record.total_repaid += repaid;
+        record.deals_completed += 1;
+    });
+
+    deal.status = DealStatus::Completed;
+    put_deal(env, deal);
+    env.events()
+        .publish((Symbol::new(env, "deal_completed"), deal.id), timestamp);
+    allocation
+}
+
+fn get_deal_totals(env: &Env, deal_id: DealId) -> DealTotals {
+    if let Some(totals) = env
+        .storage()
+        .persistent()
+        .get::<_, DealTotals>(&DataKey::DealTotals(deal_id))
+    {
+        return totals;
+    }
+
+    // Deals whose receipts predate the running totals are summed once here
+    let mut totals = DealTotals {
+        total_paid: 0,
+        receipt_count: 0,
+        last_payment_at: None,
+        by_kind: Map::new(env),
+    };
+    for r in get_receipts(env, deal_id).iter() {
+        add_to_totals(&mut totals, &r);
+    }
+    totals
+}
+
+fn add_to_totals(totals: &mut DealTotals, receipt: &Receipt) {
+    totals.receipt_count += 1;
+    let kind_total = totals.by_kind.get(receipt.kind).unwrap_or(0);
+    totals.by_kind.set(receipt.kind, kind_total + receipt.amount);
+    if matches!(receipt.kind, ReceiptKind::TenantRepayment | ReceiptKind::EarlySettlement) {
+        totals.total_paid += receipt.amount;
+        // Reversal entries (negative amounts) are not payments
+        if receipt.amount > 0 && totals.last_payment_at.is_none_or(|last| receipt.timestamp > last) {
+            totals.last_payment_at = Some(receipt.timestamp);
         }
+    }
+}
+
+fn get_fee_log(env: &Env, deal_id: DealId) -> Vec<FeeEntry> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::FeeLog(deal_id))
+        .unwrap_or_else(|| vec![env])
+}
+
+fn get_tenant_deals(env: &Env, tenant: &Address) -> Vec<DealId> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::TenantDeals(tenant.clone()))
+        .unwrap_or_else(|| vec![env])
+}
+
+/// How much a receipt reduced what the tenant owes (negative for reversals)
+///
+/// Version 1 receipts predate allocations and went entirely to the schedule, as
+/// did reversing one; any other receipt counts what it was allocated, which is
+/// nothing for a payment the deal could not absorb.
+fn balance_effect(receipt: &Receipt, receipts: &Vec<Receipt>) -> i128 {
+    if !matches!(receipt.kind, ReceiptKind::TenantRepayment | ReceiptKind::EarlySettlement) {
+        return 0;
+    }
+    let version_1 = match receipt.reverses {
+        Some(id) => receipts.get((id - 1) as u32).is_some_and(|original| original.version == 1),
+        None => receipt.version == 1,
+    };
+    if version_1 {
+        return receipt.amount;
+    }
+    let allocations = &receipt.allocations;
+    allocations.fee + allocations.arrears + allocations.current + allocations.prepayment
+}
 
-        // Extract the page
-        let limit_u32 = limit;
-        let receipts_len_u32 = sorted_receipts.len();
-        let end_index = if start_index + limit_u32 < receipts_len_u32 {
-            start_index + limit_u32
+/// Replay a deal's fees and receipts up to `period_end`, summarising those
+/// from `period_start` on
+fn build_statement(env: &Env, deal: &Deal, period_start: Timestamp, period_end: Timestamp) -> Statement {
+    let mut statement = Statement {
+        deal_id: deal.id,
+        period_start,
+        period_end,
+        opening_balance: 0,
+        advanced: 0,
+        fees_accrued: 0,
+        fees: vec![env],
+        payments: 0,
+        discounts: 0,
+        allocations: Allocation::none(),
+        receipts: vec![env],
+        closing_balance: 0,
+    };
+
+    let mut balance = 0;
+    if deal.created_at < period_start {
+        balance = deal.principal;
+    } else if deal.created_at <= period_end {
+        statement.advanced = deal.principal;
+    }
+
+    // Fees before receipts at the same timestamp, as a settlement clears them
+    let fee_log = get_fee_log(env, deal.id);
+    let receipts = get_receipts(env, deal.id);
+    let mut next_fee = 0;
+    let mut next_receipt = 0;
+    loop {
+        let fee = fee_log.get(next_fee);
+        let receipt = receipts.get(next_receipt);
+        let take_fee = match (&fee, &receipt) {
+            (Some(f), Some(r)) => f.timestamp <= r.timestamp,
+            (Some(_), None) => true,
+            (None, Some(_)) => false,
+            (None, None) => break,
+        };
+        let timestamp = if take_fee {
+            fee.as_ref().unwrap().timestamp
         } else {
-            receipts_len_u32
+            receipt.as_ref().unwrap().timestamp
         };
-
-        let mut page_receipts = vec![&env];
-        for i in start_index..end_index {
-            if let Some(receipt) = sorted_receipts.get(i) {
-                page_receipts.push_back(receipt.clone());
-            }
+        if timestamp > period_end {
+            break;
         }
+        let in_period = timestamp >= period_start;
 
-        // Determine next cursor
-        let empty_tx_id = BytesN::from_array(&env, &[0u8; 32]);
-        let (has_next, next_cursor) = if end_index < receipts_len_u32 && page_receipts.len() > 0 {
-            // There are more receipts, create cursor from the last item in this page
-            let last_index = if page_receipts.len() > 0 {
-                page_receipts.len() - 1
-            } else {
-                0
-            };
-            if let Some(last_receipt) = page_receipts.get(last_index) {
-                (true, Cursor {
-                    timestamp: last_receipt.timestamp,
-                    tx_id: last_receipt.tx_id.clone(),
-                })
+        if take_fee {
+            let fee = fee.unwrap();
+            next_fee += 1;
+            if in_period {
+                statement.fees_accrued += fee.amount;
+                statement.fees.push_back(fee.clone());
             } else {
-                // Fallback: create empty cursor
-                (false, Cursor {
-                    timestamp: 0,
-                    tx_id: empty_tx_id,
-                })
+                balance += fee.amount;
             }
+            continue;
+        }
+
+        let receipt = receipt.unwrap();
+        next_receipt += 1;
+        let paid = balance_effect(&receipt, &receipts);
+        // A settlement clears the balance; whatever it did not cover was discounted
+        let discount = if receipt.kind == ReceiptKind::EarlySettlement {
+            balance + statement.advanced + statement.fees_accrued
+                - statement.payments
+                - statement.discounts
+                - paid
         } else {
-            // No more receipts
-            (false, Cursor {
-                timestamp: 0,
-                tx_id: empty_tx_id,
-            })
+            0
         };
+        if in_period {
+            statement.payments += paid;
+            statement.discounts += discount;
+            statement.allocations.fee += receipt.allocations.fee;
+            statement.allocations.arrears += receipt.allocations.arrears;
+            statement.allocations.current += receipt.allocations.current;
+            statement.allocations.prepayment += receipt.allocations.prepayment;
+            statement.receipts.push_back(receipt);
+        } else {
+            balance -= paid + discount;
+        }
+    }
+
+    statement.opening_balance = balance;
+    statement.closing_balance = balance + statement.advanced + statement.fees_accrued
+        - statement.payments
+        - statement.discounts;
+    statement
+}
+
+fn get_fees_due(env: &Env, deal_id: DealId) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::FeesDue(deal_id))
+        .unwrap_or(0)
+}
+
+fn put_fees_due(env: &Env, deal_id: DealId, fees: i128) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::FeesDue(deal_id), &fees);
+}
+
+fn payoff_quote(env: &Env, deal: &Deal) -> PayoffQuote {
+    if deal.status != DealStatus::Active {
+        panic!("deal is not active");
+    }
+    let outstanding = outstanding_of(&get_schedule(env, deal.id));
+    let fees = get_fees_due(env, deal.id);
+    let discount_bps: u32 = env
+        .storage()
+        .persistent()
+        .get(&DataKey::SettlementDiscount(deal.id))
+        .unwrap_or(0);
+    let discount = outstanding * discount_bps as i128 / BPS_SCALE;
+    PayoffQuote {
+        deal_id: deal.id,
+        outstanding,
+        fees,
+        discount,
+        amount: outstanding + fees - discount,
+    }
+}
+
+fn put_deal_totals(env: &Env, deal_id: DealId, totals: &DealTotals) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::DealTotals(deal_id), totals);
+}
 
-        ReceiptPage {
-            receipts: page_receipts,
-            has_next,
-            next_cursor,
+/// What each installment received between two versions of a schedule, by period
+fn paid_between(env: &Env, before: &Vec<Installment>, after: &Vec<Installment>) -> Map<u32, i128> {
+    let mut paid = Map::new(env);
+    for (old, new) in before.iter().zip(after.iter()) {
+        if new.paid > old.paid {
+            paid.set(new.period, new.paid - old.paid);
         }
     }
+    paid
+}
 
-    /// Get the total number of receipts for a deal
-    pub fn receipt_count(env: Env, deal_id: DealId) -> u64 {
-        get_receipt_count(&env, deal_id)
+/// Take a reversed repayment back off the schedule, latest installments first
+///
+/// `paid_by_period` is what the reversed receipt paid each installment; those
+/// payments are undone first. Anything left over, for version 1 receipts that
+/// predate per-installment tracking, comes off the latest paid installments.
+fn unapply_from_schedule(env: &Env, deal: &mut Deal, amount: i128, paid_by_period: &Map<u32, i128>) {
+    let before = get_schedule(env, deal.id);
+    let mut schedule = before.clone();
+    let mut remaining = amount;
+    let mut unpay = |schedule: &mut Vec<Installment>, i: u32, limit: i128| {
+        let mut installment = schedule.get(i).unwrap();
+        let removed = remaining.min(installment.paid).min(limit);
+        if removed > 0 {
+            installment.paid -= removed;
+            installment.paid_at = None;
+            remaining -= removed;
+            schedule.set(i, installment);
+        }
+    };
+    for i in 0..schedule.len() {
+        let period = schedule.get(i).unwrap().period;
+        if let Some(paid) = paid_by_period.get(period) {
+            unpay(&mut schedule, i, paid);
+        }
+    }
+    for i in (0..schedule.len()).rev() {
+        unpay(&mut schedule, i, i128::MAX);
+    }
+    put_schedule(env, deal, &schedule);
+    track_installments(env, &deal.tenant, &before, &schedule);
+
+    if deal.status == DealStatus::Completed {
+        deal.status = DealStatus::Active;
+        put_deal(env, deal);
+        update_tenant_record(env, &deal.tenant, |record| {
+            record.deals_completed = record.deals_completed.saturating_sub(1);
+        });
     }
 }
 
-#[cfg(test)]
-mod test {
-    extern crate std;
+fn get_chain_head(env: &Env, deal_id: DealId) -> BytesN<32> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::ChainHead(deal_id))
+        .unwrap_or_else(|| BytesN::from_array(env, &[0u8; 32]))
+}
+
+/// The receipt exactly as it was first recorded
+///
+/// `reversed_by` is the only field written after a receipt is appended, and
+/// `chain_hash` is derived from this form, so both are cleared.
+fn as_recorded(receipt: &Receipt) -> Receipt {
+    let mut recorded = receipt.clone();
+    recorded.reversed_by = None;
+    recorded.chain_hash = None;
+    recorded
+}
+
+/// Advance the deal's hash chain over a new receipt and return the new head:
+/// `sha256(previous_head || xdr(as_recorded(receipt)))`
+fn extend_chain(env: &Env, receipt: &Receipt) -> BytesN<32> {
+    let mut preimage = Bytes::from_array(env, &get_chain_head(env, receipt.deal_id).to_array());
+    preimage.append(&as_recorded(receipt).to_xdr(env));
+    let head: BytesN<32> = env.crypto().sha256(&preimage).into();
+    env.storage()
+        .persistent()
+        .set(&DataKey::ChainHead(receipt.deal_id), &head);
+    head
+}
+
+/// Domain separation prefixes for the receipt Merkle mountain range
+pub const MMR_LEAF_PREFIX: u8 = 0;
+pub const MMR_NODE_PREFIX: u8 = 1;
+pub const MMR_ROOT_PREFIX: u8 = 2;
+
+/// Find the mountain holding `leaf_index` in an accumulator of `leaf_count`
+/// leaves, returned as (peak position, mountain height, index within mountain)
+///
+/// Mountains are perfect binary trees, one per set bit of `leaf_count`,
+/// ordered from the tallest (oldest leaves) to the shortest.
+pub fn locate_leaf(leaf_index: u64, leaf_count: u64) -> Option<(u32, u32, u64)> {
+    let mut offset = 0u64;
+    let mut position = 0u32;
+    for height in (0..64u32).rev() {
+        if leaf_count & (1u64 << height) == 0 {
+            continue;
+        }
+        let size = 1u64 << height;
+        if leaf_index < offset + size {
+            return Some((position, height, leaf_index - offset));
+        }
+        offset += size;
+        position += 1;
+    }
+    None
+}
+
+fn get_leaf_count(env: &Env) -> u64 {
+    env.storage()
+        .instance()
+        .get(&DataKey::MmrLeafCount)
+        .unwrap_or(0)
+}
+
+fn get_peaks(env: &Env) -> Vec<BytesN<32>> {
+    env.storage()
+        .instance()
+        .get(&DataKey::MmrPeaks)
+        .unwrap_or_else(|| vec![env])
+}
+
+fn hash_leaf(env: &Env, receipt: &Receipt) -> BytesN<32> {
+    let mut preimage = Bytes::from_array(env, &[MMR_LEAF_PREFIX]);
+    preimage.append(&as_recorded(receipt).to_xdr(env));
+    env.crypto().sha256(&preimage).into()
+}
+
+fn hash_node(env: &Env, left: &BytesN<32>, right: &BytesN<32>) -> BytesN<32> {
+    let mut preimage = Bytes::from_array(env, &[MMR_NODE_PREFIX]);
+    preimage.append(&Bytes::from_array(env, &left.to_array()));
+    preimage.append(&Bytes::from_array(env, &right.to_array()));
+    env.crypto().sha256(&preimage).into()
+}
+
+/// `sha256(0x02 || leaf_count as u64 big-endian || peak_0 || ... || peak_n)`
+fn bag_peaks(env: &Env, leaf_count: u64, peaks: &Vec<BytesN<32>>) -> BytesN<32> {
+    let mut preimage = Bytes::from_array(env, &[MMR_ROOT_PREFIX]);
+    preimage.append(&Bytes::from_array(env, &leaf_count.to_be_bytes()));
+    for peak in peaks.iter() {
+        preimage.append(&Bytes::from_array(env, &peak.to_array()));
+    }
+    env.crypto().sha256(&preimage).into()
+}
+
+/// Root kept for `leaf_count`; the current root is always available, as are
+/// those of counts reached after roots were first kept per count
+fn get_root_at(env: &Env, leaf_count: u64) -> Option<BytesN<32>> {
+    if leaf_count == get_leaf_count(env) {
+        return Some(bag_peaks(env, leaf_count, &get_peaks(env)));
+    }
+    env.storage().persistent().get(&DataKey::MmrRoot(leaf_count))
+}
+
+/// Append a receipt to the Merkle mountain range, merging equal-height peaks
+fn append_leaf(env: &Env, receipt: &Receipt) {
+    let leaf_count = get_leaf_count(env);
+    let mut peaks = get_peaks(env);
+    let leaf = hash_leaf(env, receipt);
+
+    let mut node = leaf.clone();
+    let mut height = 0;
+    while leaf_count & (1u64 << height) != 0 {
+        let left = peaks.pop_back().unwrap();
+        node = hash_node(env, &left, &node);
+        height += 1;
+    }
+    peaks.push_back(node);
+
+    let storage = env.storage().instance();
+    storage.set(&DataKey::MmrPeaks, &peaks);
+    storage.set(&DataKey::MmrLeafCount, &(leaf_count + 1));
+    env.storage()
+        .persistent()
+        .set(&DataKey::MmrRoot(leaf_count + 1), &bag_peaks(env, leaf_count + 1, &peaks));
+
+    env.events().publish(
+        (Symbol::new(env, "receipt_leaf"), receipt.deal_id),
+        (receipt.id, leaf_count, leaf),
+    );
+}
+
+/// Part of a receipt's payment that goes to its deal's schedule, which
+/// NGN-denominated deals keep in kobo: their receipts must carry `amount_ngn`
+fn scheduled_amount(env: &Env, deal_id: DealId, amount: i128, amount_ngn: Option<i128>) -> i128 {
+    match (get_deal_fx(env, deal_id), amount_ngn) {
+        (Some(_), Some(amount_ngn)) => amount_ngn,
+        (Some(_), None) => panic!("amount_ngn required for NGN deals"),
+        (None, _) => amount,
+    }
+}
+
+/// Record a receipt, paying down the deal's schedule for repayments
+///
+/// With `strict`, a repayment on a deal that is not active, or for more than it
+/// owes, is rejected. Otherwise the receipt is recorded regardless and only what
+/// the deal still owes is applied to it.
+#[allow(clippy::too_many_arguments)]
+fn store_receipt(
+    env: &Env,
+    deal_id: DealId,
+    kind: ReceiptKind,
+    amount: i128,
+    payer: Address,
+    details: ReceiptDetails,
+    protocol_fee: i128,
+    strict: bool,
+) -> Receipt {
+    if amount <= 0 {
+        panic!("amount must be positive");
+    }
+    if let Some(hash) = &details.external_ref_hash {
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::ReceiptByExternalRef(hash.clone()))
+        {
+            panic!("external reference already recorded");
+        }
+    }
+
+    let timestamp = env.ledger().timestamp();
+    let mut totals = get_deal_totals(env, deal_id);
+
+    // Repayments and settlements on a registered deal pay down its schedule
+    let mut allocations = Allocation::none();
+    let mut paid_by_period = Map::new(env);
+    if matches!(kind, ReceiptKind::TenantRepayment | ReceiptKind::EarlySettlement) {
+        if let Some(mut deal) = get_deal(env, deal_id) {
+            let scheduled = scheduled_amount(env, deal_id, amount, details.amount_ngn);
+            if deal.status != DealStatus::Active {
+                if strict {
+                    panic!("deal is not active");
+                }
+            } else if kind == ReceiptKind::EarlySettlement {
+                allocations = settle_schedule(env, &mut deal, scheduled, amount, timestamp);
+            } else {
+                let owed = outstanding_of(&get_schedule(env, deal_id)) + get_fees_due(env, deal_id);
+                let applied = if strict { scheduled } else { scheduled.min(owed) };
+                if applied > 0 {
+                    let before = get_schedule(env, deal_id);
+                    let repaid = amount * applied / scheduled;
+                    allocations = apply_to_schedule(env, &mut deal, applied, repaid, timestamp);
+                    paid_by_period = paid_between(env, &before, &get_schedule(env, deal_id));
+                }
+            }
+        }
+    }
+
+    let receipt_id = increment_receipt_count(env, deal_id);
+    let tx_id = get_tx_id(env);
+    if !paid_by_period.is_empty() {
+        env.storage()
+            .persistent()
+            .set(&DataKey::ReceiptInstallments(deal_id, receipt_id), &paid_by_period);
+    }
+
+    let receipt = Receipt {
+        version: RECEIPT_VERSION,
+        id: receipt_id,
+        deal_id,
+        kind,
+        amount,
+        timestamp,
+        tx_id,
+        payer: payer.clone(),
+        external_ref_hash: details.external_ref_hash,
+        token: details.token,
+        amount_ngn: details.amount_ngn,
+        fx_rate: details.fx_rate,
+        fx_provider: details.fx_provider,
+        from: details.from,
+        to: details.to,
+        metadata_hash: details.metadata_hash,
+        reverses: None,
+        reversed_by: None,
+        reversal_reason: None,
+        chain_hash: None,
+        leaf_index: None,
+        allocations,
+        protocol_fee,
+    };
+
+    let mut receipt = receipt;
+    receipt.leaf_index = Some(get_leaf_count(env));
+    receipt.chain_hash = Some(extend_chain(env, &receipt));
+    append_leaf(env, &receipt);
+
+    let mut receipts = get_receipts(env, deal_id);
+    receipts.push_back(receipt.clone());
+    put_receipts(env, deal_id, receipts);
+    index_receipt(env, &receipt);
+
+    add_to_totals(&mut totals, &receipt);
+    put_deal_totals(env, deal_id, &totals);
+
+    env.events().publish(
+        (Symbol::new(env, "receipt_created"), deal_id),
+        (receipt_id, amount, payer),
+    );
+
+    receipt
+}
+
+/// Order two receipts by (timestamp, tx_id)
+fn cmp_receipts(a: &Receipt, b: &Receipt) -> core::cmp::Ordering {
+    cmp_to_cursor(a, &Cursor {
+        timestamp: b.timestamp,
+        tx_id: b.tx_id.clone(),
+    })
+}
+
+/// Order a receipt relative to a cursor position by (timestamp, tx_id)
+fn cmp_to_cursor(r: &Receipt, cursor: &Cursor) -> core::cmp::Ordering {
+    match r.timestamp.cmp(&cursor.timestamp) {
+        core::cmp::Ordering::Equal => r.tx_id.to_array().cmp(&cursor.tx_id.to_array()),
+        ordering => ordering,
+    }
+}
+
+fn cursor_of(r: &Receipt) -> Cursor {
+    Cursor {
+        timestamp: r.timestamp,
+        tx_id: r.tx_id.clone(),
+    }
+}
+
+fn validate_page_request(limit: u32, query: &ReceiptQuery) {
+    if limit == 0 || limit > 100 {
+        panic!("limit must be between 1 and 100");
+    }
+    if let (Some(from), Some(to)) = (query.from_timestamp, query.to_timestamp) {
+        if from > to {
+            panic!("from_timestamp must not be after to_timestamp");
+        }
+    }
+}
+
+/// Sort receipts by (timestamp, tx_id), apply `query` and return the page after `cursor`
+fn paginate_receipts(
+    env: &Env,
+    receipts: Vec<Receipt>,
+    limit: u32,
+    cursor: Option<Cursor>,
+    query: &ReceiptQuery,
+) -> ReceiptPage {
+    validate_page_request(limit, query);
+
+    // Keep only receipts inside the requested time range
+    let mut sorted_receipts = vec![env];
+    for r in receipts.iter() {
+        let after_start = query.from_timestamp.is_none_or(|from| r.timestamp >= from);
+        let before_end = query.to_timestamp.is_none_or(|to| r.timestamp <= to);
+        if after_start && before_end {
+            sorted_receipts.push_back(r);
+        }
+    }
+
+    // Sort receipts by (timestamp, tx_id) in ascending order
+    // We'll use a simple bubble sort since we can't use sort_by in no_std easily
+    // For production, consider using a more efficient algorithm or storing sorted
+    if sorted_receipts.len() > 1 {
+        let mut swapped = true;
+        while swapped {
+            swapped = false;
+            let len = sorted_receipts.len();
+            for i in 0..(len - 1) {
+                let a = sorted_receipts.get(i).unwrap();
+                let b = sorted_receipts.get(i + 1).unwrap();
+
+                if cmp_receipts(&a, &b) == core::cmp::Ordering::Greater {
+                    sorted_receipts.set(i, b);
+                    sorted_receipts.set(i + 1, a);
+                    swapped = true;
+                }
+            }
+        }
+    }
+
+    if query.descending {
+        let mut reversed = vec![env];
+        for r in sorted_receipts.iter().rev() {
+            reversed.push_back(r);
+        }
+        sorted_receipts = reversed;
+    }
+
+    // Find the first receipt strictly past the cursor in the requested direction
+    let total = sorted_receipts.len();
+    let mut start_index = 0u32;
+    if let Some(cursor) = cursor {
+        let past_cursor = if query.descending {
+            core::cmp::Ordering::Less
+        } else {
+            core::cmp::Ordering::Greater
+        };
+        start_index = total;
+        for i in 0..total {
+            if cmp_to_cursor(&sorted_receipts.get(i).unwrap(), &cursor) == past_cursor {
+                start_index = i;
+                break;
+            }
+        }
+    }
+
+    // Extract the page
+    let end_index = if start_index + limit < total {
+        start_index + limit
+    } else {
+        total
+    };
+
+    let mut page_receipts = vec![env];
+    for i in start_index..end_index {
+        page_receipts.push_back(sorted_receipts.get(i).unwrap());
+    }
+
+    // Cursors are only meaningful when the matching has_* flag is set
+    let empty_cursor = Cursor {
+        timestamp: 0,
+        tx_id: BytesN::from_array(env, &[0u8; 32]),
+    };
+    let (has_next, next_cursor) = match page_receipts.last() {
+        Some(last) if end_index < total => (true, cursor_of(&last)),
+        _ => (false, empty_cursor.clone()),
+    };
+    let (has_prev, prev_cursor) = match page_receipts.first() {
+        Some(first) if start_index > 0 => (true, cursor_of(&first)),
+        _ => (false, empty_cursor),
+    };
+
+    ReceiptPage {
+        receipts: page_receipts,
+        has_next,
+        next_cursor,
+        has_prev,
+        prev_cursor,
+    }
+}
+
+#[contractimpl]
+impl RentPayments {
+    pub fn init(env: Env, admin: Address) {
+        if env.storage().instance().has(&DataKey::Admin) {
+            panic!("already initialized");
+        }
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.events().publish((Symbol::new(&env, "init"),), admin);
+    }
+
+    /// Send `cut_bps` of every payment collected by `pay`, `execute_due` and
+    /// `settle_early` to the default reserve
+    pub fn set_reserve(env: Env, reserve: Address, cut_bps: u32) {
+        require_admin(&env);
+        if cut_bps as i128 >= BPS_SCALE {
+            panic!("cut_bps must be below 10000");
+        }
+        env.storage().instance().set(&DataKey::Reserve, &reserve);
+        env.storage().instance().set(&DataKey::ReserveCutBps, &cut_bps);
+        env.events()
+            .publish((Symbol::new(&env, "set_reserve"),), (reserve, cut_bps));
+    }
+
+    /// Unpaid principal across all active deals denominated in their token;
+    /// NGN-denominated deals are left out (see `deal_position` for their value)
+    pub fn total_outstanding(env: Env) -> i128 {
+        get_total_outstanding(&env)
+    }
+
+    /// Set the protocol treasury, which receives tenant payments for deals
+    /// without their own recipient
+    pub fn set_treasury(env: Env, treasury: Address) {
+        require_admin(&env);
+        env.storage().instance().set(&DataKey::Treasury, &treasury);
+        env.events()
+            .publish((Symbol::new(&env, "set_treasury"),), treasury);
+    }
+
+    pub fn treasury(env: Env) -> Option<Address> {
+        get_treasury(&env)
+    }
+
+    /// Charge a protocol fee on payments of `kind` collected by the contract,
+    /// or stop charging one with `None` (admin-auth)
+    ///
+    /// Only `TenantRepayment` and `EarlySettlement` payments move funds. The
+    /// fee is withheld from what the deal's recipient receives and sent to the
+    /// treasury; the tenant is credited with the full payment.
+    pub fn set_protocol_fee(env: Env, kind: ReceiptKind, fee: Option<FeeRate>) {
+        require_admin(&env);
+        if !matches!(kind, ReceiptKind::TenantRepayment | ReceiptKind::EarlySettlement) {
+            panic!("protocol fees only apply to repayments and settlements");
+        }
+        if let Some(rate) = &fee {
+            rate.validate();
+            env.storage().instance().set(&DataKey::ProtocolFee(kind), rate);
+        } else {
+            env.storage().instance().remove(&DataKey::ProtocolFee(kind));
+        }
+        env.events()
+            .publish((Symbol::new(&env, "set_protocol_fee"), kind), fee);
+    }
+
+    pub fn protocol_fee(env: Env, kind: ReceiptKind) -> Option<FeeRate> {
+        get_protocol_fee(&env, kind)
+    }
+
+    /// Protocol fees paid to the treasury so far, net of reversals, by token
+    pub fn fees_collected(env: Env) -> Map<Address, i128> {
+        get_fees_collected(&env)
+    }
+
+    /// Register a financed deal and generate its repayment schedule
+    ///
+    /// `principal` is split into `term` (at most `MAX_TERM`) installments due
+    /// every `INSTALLMENT_INTERVAL` (30 days, not calendar months) starting at
+    /// `first_due`.
+    pub fn create_deal(
+        env: Env,
+        deal_id: DealId,
+        tenant: Address,
+        landlord: Address,
+        principal: i128,
+        term: u32,
+        first_due: Timestamp,
+    ) -> Deal {
+        require_admin(&env);
+
+        if get_deal(&env, deal_id).is_some() {
+            panic!("deal already exists");
+        }
+        if principal <= 0 {
+            panic!("principal must be positive");
+        }
+        if term == 0 {
+            panic!("term must be positive");
+        }
+        if term > MAX_TERM {
+            panic!("term exceeds MAX_TERM");
+        }
+
+        let deal = Deal {
+            id: deal_id,
+            tenant: tenant.clone(),
+            landlord,
+            principal,
+            term,
+            status: DealStatus::Active,
+            created_at: env.ledger().timestamp(),
+            token: None,
+            recipient: None,
+            schedule_version: 1,
+            allocation_policy: AllocationPolicy::FeesFirst,
+            listing_id: None,
+        };
+        put_deal(&env, &deal);
+        put_schedule(&env, &deal, &build_schedule(&env, principal, term, first_due));
+        let mut tenant_deals = get_tenant_deals(&env, &tenant);
+        tenant_deals.push_back(deal_id);
+        env.storage()
+            .persistent()
+            .set(&DataKey::TenantDeals(tenant.clone()), &tenant_deals);
+
+        env.events().publish(
+            (Symbol::new(&env, "deal_created"), deal_id),
+            (tenant, principal, term),
+        );
+
+        deal
+    }
+
+    /// Reschedule a deal's unpaid balance over `new_term` installments
+    ///
+    /// Fully paid installments are kept as they are. A partially paid
+    /// installment is closed at the amount already paid, and the whole unpaid
+    /// balance is split into `new_term` installments due every 30 days from
+    /// `first_due`, numbered after the kept ones; `first_due` may not be in the
+    /// past or before a kept installment. The replaced schedule stays readable
+    /// through `get_prior_schedule` under the previous version.
+    pub fn restructure_deal(
+        env: Env,
+        deal_id: DealId,
+        new_term: u32,
+        first_due: Timestamp,
+    ) -> Vec<Installment> {
+        require_admin(&env);
+
+        let mut deal = get_deal(&env, deal_id).expect("deal not found");
+        if deal.status != DealStatus::Active {
+            panic!("deal is not active");
+        }
+        if new_term == 0 {
+            panic!("term must be positive");
+        }
+        if new_term > MAX_TERM {
+            panic!("term exceeds MAX_TERM");
+        }
+
+        let old_schedule = get_schedule(&env, deal_id);
+        let remaining = outstanding_of(&old_schedule);
+        let now = env.ledger().timestamp();
+        if first_due < now {
+            panic!("first_due must not be in the past");
+        }
+
+        // Paid installments are kept; partially paid ones are closed at what
+        // was paid, which counts towards the tenant's record like any payment
+        let mut schedule = vec![&env];
+        let mut open = vec![&env];
+        let mut closed = vec![&env];
+        for mut installment in old_schedule.iter() {
+            if installment.paid == 0 {
+                continue;
+            }
+            if first_due < installment.due_date {
+                panic!("first_due must not precede a paid installment");
+            }
+            if installment.paid < installment.amount {
+                open.push_back(installment.clone());
+                installment.amount = installment.paid;
+                installment.paid_at = Some(now);
+                closed.push_back(installment.clone());
+            }
+            schedule.push_back(installment);
+        }
+        track_installments(&env, &deal.tenant, &open, &closed);
+        let kept = schedule.len();
+        for mut installment in build_schedule(&env, remaining, new_term, first_due).iter() {
+            installment.period += kept;
+            schedule.push_back(installment);
+        }
+
+        let old_version = deal.schedule_version;
+        env.storage()
+            .persistent()
+            .set(&DataKey::PriorSchedule(deal_id, old_version), &old_schedule);
+        put_schedule(&env, &deal, &schedule);
+
+        let old_term = deal.term;
+        deal.term = schedule.len();
+        deal.schedule_version += 1;
+        put_deal(&env, &deal);
+
+        let total_of = |s: &Vec<Installment>| -> i128 { s.iter().map(|i| i.amount).sum() };
+        env.events().publish(
+            (Symbol::new(&env, "deal_restructured"), deal_id),
+            (
+                deal.schedule_version,
+                (old_term, total_of(&old_schedule)),
+                (deal.term, total_of(&schedule)),
+            ),
+        );
+
+        schedule
+    }
+
+    /// Get a schedule that a restructure replaced (`version` < the deal's
+    /// current `schedule_version`)
+    pub fn get_prior_schedule(env: Env, deal_id: DealId, version: u32) -> Vec<Installment> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::PriorSchedule(deal_id, version))
+            .expect("schedule version not found")
+    }
+
+    /// Choose the order in which a deal's repayments cover fees and installments
+    pub fn set_allocation_policy(env: Env, deal_id: DealId, policy: AllocationPolicy) {
+        require_admin(&env);
+        let mut deal = get_deal(&env, deal_id).expect("deal not found");
+        deal.allocation_policy = policy;
+        put_deal(&env, &deal);
+        env.events().publish(
+            (Symbol::new(&env, "allocation_policy_set"), deal_id),
+            policy,
+        );
+    }
+
+    /// Mark an active deal as defaulted
+    ///
+    /// Its unpaid installments count against the tenant's record, and no
+    /// further repayments are accepted.
+    pub fn mark_default(env: Env, deal_id: DealId) {
+        require_admin(&env);
+        let mut deal = get_deal(&env, deal_id).expect("deal not found");
+        if deal.status != DealStatus::Active {
+            panic!("deal is not active");
+        }
+
+        let schedule = get_schedule(&env, deal_id);
+        let unpaid = schedule.iter().filter(|i| i.paid < i.amount).count() as u32;
+        let outstanding = outstanding_of(&schedule);
+        if in_total_outstanding(&env, &deal) {
+            adjust_total_outstanding(&env, -outstanding);
+        }
+        deal.status = DealStatus::Defaulted;
+        put_deal(&env, &deal);
+        update_tenant_record(&env, &deal.tenant, |record| {
+            record.installments_defaulted += unpaid;
+            record.deals_defaulted += 1;
+        });
+
+        env.events().publish(
+            (Symbol::new(&env, "deal_defaulted"), deal_id),
+            (deal.tenant, outstanding, unpaid),
+        );
+    }
+
+    /// Repayment history of a tenant across all deals, for landlords and
+    /// other contracts assessing creditworthiness
+    pub fn tenant_record(env: Env, tenant: Address) -> TenantRecord {
+        get_tenant_record(&env, &tenant)
+    }
+
+    /// Set the listings contract that deals can be linked to
+    pub fn set_listings_contract(env: Env, listings: Address) {
+        require_admin(&env);
+        env.storage()
+            .instance()
+            .set(&DataKey::ListingsContract, &listings);
+        env.events()
+            .publish((Symbol::new(&env, "set_listings_contract"),), listings);
+    }
+
+    /// Link a deal to the verified whistleblower listing it finances
+    ///
+    /// The listings contract checks that the listing is approved and marks it
+    /// as rented by this deal.
+    pub fn link_listing(env: Env, deal_id: DealId, listing_id: u64) {
+        require_admin(&env);
+        let mut deal = get_deal(&env, deal_id).expect("deal not found");
+        if deal.listing_id.is_some() {
+            panic!("deal already linked to a listing");
+        }
+        let listings: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::ListingsContract)
+            .expect("listings contract not set");
+
+        ListingsClient::new(&env, &listings).mark_rented(&listing_id, &deal_id);
+        deal.listing_id = Some(listing_id);
+        put_deal(&env, &deal);
+
+        env.events()
+            .publish((Symbol::new(&env, "listing_linked"), deal_id), listing_id);
+    }
+
+    /// Configure how tenants pay a deal through `pay`: the token contract and,
+    /// optionally, the recipient (the protocol treasury when `None`)
+    pub fn set_deal_payment(env: Env, deal_id: DealId, token: Address, recipient: Option<Address>) {
+        require_admin(&env);
+        let mut deal = get_deal(&env, deal_id).expect("deal not found");
+        deal.token = Some(token.clone());
+        deal.recipient = recipient.clone();
+        put_deal(&env, &deal);
+        env.events().publish(
+            (Symbol::new(&env, "deal_payment_set"), deal_id),
+            (token, recipient),
+        );
+    }
+
+    /// Denominate a deal in NGN, priced in its token by `oracle` (admin-auth)
+    ///
+    /// The deal's principal and schedule are taken to be in kobo. From then
+    /// on, `pay`, `execute_due` and `settle_early` take amounts in kobo and
+    /// transfer the token amount at the oracle's median rate for `pair`,
+    /// panicking if fewer than `min_reports` reports are at most `max_age`
+    /// seconds old. Must be set before the deal's first receipt; the deal then
+    /// leaves `total_outstanding`.
+    pub fn set_deal_fx(
+        env: Env,
+        deal_id: DealId,
+        oracle: Address,
+        pair: Symbol,
+        max_age: u64,
+        min_reports: u32,
+    ) {
+        require_admin(&env);
+        let deal = get_deal(&env, deal_id).expect("deal not found");
+        if get_receipt_count(&env, deal_id) > 0 {
+            panic!("deal already has receipts");
+        }
+        if min_reports == 0 {
+            panic!("min_reports must be positive");
+        }
+        if in_total_outstanding(&env, &deal) {
+            adjust_total_outstanding(&env, -outstanding_of(&get_schedule(&env, deal_id)));
+        }
+        let fx = DealFx {
+            oracle,
+            pair,
+            max_age,
+            min_reports,
+        };
+        env.storage().persistent().set(&DataKey::DealFx(deal_id), &fx);
+        env.events()
+            .publish((Symbol::new(&env, "deal_fx_set"), deal_id), fx);
+    }
+
+    pub fn deal_fx(env: Env, deal_id: DealId) -> Option<DealFx> {
+        get_deal_fx(&env, deal_id)
+    }
+
+    /// Token amount a payment of `amount` (in the deal's units) would transfer
+    /// now
+    pub fn quote_payment(env: Env, deal_id: DealId, amount: i128) -> i128 {
+        let deal = get_deal(&env, deal_id).expect("deal not found");
+        quote_payment(&env, &deal, amount)
+    }
+
+    /// Pay towards a deal directly from the payer's account
+    ///
+    /// Unlike `create_receipt`, where the admin asserts who paid, the payer
+    /// authorizes this call and `amount` of the deal's token is transferred
+    /// from them to the deal's recipient (or the protocol treasury). The
+    /// resulting `TenantRepayment` receipt is applied to the schedule as usual.
+    pub fn pay(env: Env, deal_id: DealId, payer: Address, amount: i128) -> Receipt {
+        payer.require_auth();
+
+        if amount <= 0 {
+            panic!("amount must be positive");
+        }
+        let deal = get_deal(&env, deal_id).expect("deal not found");
+        collect_payment(&env, &deal, ReceiptKind::TenantRepayment, payer, amount, false)
+    }
+
+    /// Authorize automatic collection of a deal's installments
+    ///
+    /// Signed once by the tenant. Until `valid_until`, anyone may call
+    /// `execute_due` to collect an installment that has fallen due, provided
+    /// it costs no more than `max_per_installment` of the deal's token.
+    /// Replaces any existing mandate for the deal.
+    pub fn create_mandate(
+        env: Env,
+        deal_id: DealId,
+        max_per_installment: i128,
+        valid_until: Timestamp,
+    ) -> Mandate {
+        let deal = get_deal(&env, deal_id).expect("deal not found");
+        deal.tenant.require_auth();
+
+        if deal.status != DealStatus::Active {
+            panic!("deal is not active");
+        }
+        let (token, recipient) = payment_route(&env, &deal);
+        if max_per_installment <= 0 {
+            panic!("max_per_installment must be positive");
+        }
+        let now = env.ledger().timestamp();
+        if valid_until <= now {
+            panic!("valid_until must be in the future");
+        }
+
+        let mandate = Mandate {
+            deal_id,
+            payer: deal.tenant,
+            token,
+            recipient,
+            max_per_installment,
+            valid_until,
+            created_at: now,
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::Mandate(deal_id), &mandate);
+        env.events().publish(
+            (Symbol::new(&env, "mandate_created"), deal_id),
+            (mandate.payer.clone(), max_per_installment, valid_until),
+        );
+        mandate
+    }
+
+    /// Cancel a deal's mandate (tenant-auth)
+    pub fn revoke_mandate(env: Env, deal_id: DealId) {
+        let mandate = get_mandate(&env, deal_id).expect("no mandate for deal");
+        mandate.payer.require_auth();
+
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Mandate(deal_id));
+        env.events()
+            .publish((Symbol::new(&env, "mandate_revoked"), deal_id), mandate.payer);
+    }
+
+    pub fn get_mandate(env: Env, deal_id: DealId) -> Option<Mandate> {
+        get_mandate(&env, deal_id)
+    }
+
+    /// Collect the earliest unpaid installment under the deal's mandate
+    ///
+    /// Permissionless, so a keeper can run it. The installment must be due
+    /// and the token amount for its unpaid balance within the mandate limit;
+    /// exactly that amount is pulled from the tenant and recorded as a normal
+    /// tenant repayment.
+    pub fn execute_due(env: Env, deal_id: DealId) -> Receipt {
+        let mandate = get_mandate(&env, deal_id).expect("no mandate for deal");
+        let now = env.ledger().timestamp();
+        if now > mandate.valid_until {
+            panic!("mandate expired");
+        }
+
+        let deal = get_deal(&env, deal_id).expect("deal not found");
+        if deal.status != DealStatus::Active {
+            panic!("deal is not active");
+        }
+        let (token, recipient) = payment_route(&env, &deal);
+        if token != mandate.token || recipient != mandate.recipient {
+            panic!("payment route changed since mandate");
+        }
+        let installment = get_schedule(&env, deal_id)
+            .iter()
+            .find(|i| i.paid < i.amount)
+            .expect("no installment due");
+        if installment.due_date > now {
+            panic!("no installment due");
+        }
+        let amount = installment.amount - installment.paid;
+        if to_token_amount(&env, deal_id, &token, amount).0 > mandate.max_per_installment {
+            panic!("installment exceeds mandate limit");
+        }
+
+        let receipt = collect_payment(
+            &env,
+            &deal,
+            ReceiptKind::TenantRepayment,
+            mandate.payer,
+            amount,
+            true,
+        );
+        env.events().publish(
+            (Symbol::new(&env, "mandate_executed"), deal_id),
+            (installment.period, receipt.id, amount),
+        );
+        receipt
+    }
+
+    /// Get a registered deal
+    pub fn get_deal(env: Env, deal_id: DealId) -> Deal {
+        get_deal(&env, deal_id).expect("deal not found")
+    }
+
+    /// Status of a registered deal, for contracts that only need the lifecycle
+    pub fn deal_status(env: Env, deal_id: DealId) -> DealStatus {
+        get_deal(&env, deal_id).expect("deal not found").status
+    }
+
+    /// Status, landlord and principal position of a registered deal, in its
+    /// token
+    ///
+    /// NGN-denominated deals are converted at the oracle's current rate, so
+    /// this panics while their rate is stale.
+    pub fn deal_position(env: Env, deal_id: DealId) -> DealPosition {
+        let deal = get_deal(&env, deal_id).expect("deal not found");
+        let outstanding = outstanding_of(&get_schedule(&env, deal_id));
+        DealPosition {
+            status: deal.status,
+            landlord: deal.landlord.clone(),
+            principal: quote_payment(&env, &deal, deal.principal),
+            outstanding: quote_payment(&env, &deal, outstanding),
+        }
+    }
+
+    /// Listing a registered deal was linked to with `link_listing`
+    pub fn deal_listing(env: Env, deal_id: DealId) -> Option<u64> {
+        get_deal(&env, deal_id).expect("deal not found").listing_id
+    }
+
+    /// Get a deal's repayment schedule (empty if the deal was never registered)
+    pub fn get_schedule(env: Env, deal_id: DealId) -> Vec<Installment> {
+        get_schedule(&env, deal_id)
+    }
+
+    /// Payment position of a deal in one call
+    ///
+    /// Totals come from running aggregates updated by every receipt; the
+    /// schedule fields are derived from the (short) installment list, so no
+    /// receipts are read.
+    pub fn deal_summary(env: Env, deal_id: DealId) -> DealSummary {
+        let totals = get_deal_totals(&env, deal_id);
+        let schedule = get_schedule(&env, deal_id);
+        let now = env.ledger().timestamp();
+
+        let mut next_due_date = None;
+        let mut next_due_amount = 0;
+        let mut installments_paid = 0;
+        let mut installments_late = 0;
+        for installment in schedule.iter() {
+            if installment.paid == installment.amount {
+                installments_paid += 1;
+                continue;
+            }
+            if is_late(&installment, now) {
+                installments_late += 1;
+            }
+            if next_due_date.is_none() {
+                next_due_date = Some(installment.due_date);
+                next_due_amount = installment.amount - installment.paid;
+            }
+        }
+
+        let deal = get_deal(&env, deal_id);
+        DealSummary {
+            deal_id,
+            registered: deal.is_some(),
+            status: deal.map_or(DealStatus::Active, |deal| deal.status),
+            total_paid: totals.total_paid,
+            receipt_count: totals.receipt_count,
+            last_payment_at: totals.last_payment_at,
+            by_kind: totals.by_kind,
+            outstanding: outstanding_of(&schedule),
+            next_due_date,
+            next_due_amount,
+            installments_paid,
+            installments_late,
+            fees_due: get_fees_due(&env, deal_id),
+        }
+    }
+
+    /// Charge a fee (e.g. a late fee) to a deal
+    ///
+    /// Fees are collected by later repayments, in the order given by the deal's
+    /// allocation policy, or in full by `settle_early`.
+    pub fn assess_fee(env: Env, deal_id: DealId, amount: i128, reason: Symbol) {
+        require_admin(&env);
+        if amount <= 0 {
+            panic!("amount must be positive");
+        }
+        let deal = get_deal(&env, deal_id).expect("deal not found");
+        if deal.status != DealStatus::Active {
+            panic!("deal is not active");
+        }
+
+        let fees = get_fees_due(&env, deal_id) + amount;
+        put_fees_due(&env, deal_id, fees);
+        let mut fee_log = get_fee_log(&env, deal_id);
+        fee_log.push_back(FeeEntry {
+            amount,
+            reason: reason.clone(),
+            timestamp: env.ledger().timestamp(),
+        });
+        env.storage()
+            .persistent()
+            .set(&DataKey::FeeLog(deal_id), &fee_log);
+        env.events().publish(
+            (Symbol::new(&env, "fee_assessed"), deal_id),
+            (amount, reason, fees),
+        );
+    }
+
+    /// Set the discount, in basis points of the outstanding principal, granted
+    /// when a deal is settled early
+    pub fn set_settlement_discount(env: Env, deal_id: DealId, discount_bps: u32) {
+        require_admin(&env);
+        if discount_bps as i128 >= BPS_SCALE {
+            panic!("discount_bps must be below 10000");
+        }
+        get_deal(&env, deal_id).expect("deal not found");
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::SettlementDiscount(deal_id), &discount_bps);
+        env.events().publish(
+            (Symbol::new(&env, "settlement_discount_set"), deal_id),
+            discount_bps,
+        );
+    }
+
+    /// Amount that would settle an active deal right now
+    pub fn payoff_quote(env: Env, deal_id: DealId) -> PayoffQuote {
+        let deal = get_deal(&env, deal_id).expect("deal not found");
+        payoff_quote(&env, &deal)
+    }
+
+    /// Close a deal early by paying its current payoff quote
+    ///
+    /// Exactly the quoted amount is transferred from `payer` (who authorizes
+    /// the call) in the deal's token, as with `pay`. Remaining installments are
+    /// marked paid, fees are cleared, the deal becomes `Completed` and a single
+    /// `EarlySettlement` receipt records the payment.
+    pub fn settle_early(env: Env, deal_id: DealId, payer: Address) -> Receipt {
+        payer.require_auth();
+
+        let deal = get_deal(&env, deal_id).expect("deal not found");
+        let quote = payoff_quote(&env, &deal);
+        let receipt = collect_payment(
+            &env,
+            &deal,
+            ReceiptKind::EarlySettlement,
+            payer,
+            quote.amount,
+            false,
+        );
+        env.events().publish(
+            (Symbol::new(&env, "deal_settled"), deal_id),
+            (receipt.id, quote.outstanding, quote.fees, quote.discount),
+        );
+        receipt
+    }
+
+    /// Create a new receipt for a deal
+    /// This function records a monthly payment receipt
+    ///
+    /// The receipt is always recorded. On a registered, active deal it pays down
+    /// the schedule up to what the deal still owes; any excess, and payments on
+    /// a completed or defaulted deal, are recorded without an allocation.
+    /// Repayments on NGN-denominated deals need `record_receipt` with
+    /// `amount_ngn`.
+    pub fn create_receipt(
+        env: Env,
+        deal_id: DealId,
+        amount: i128,
+        payer: Address,
+    ) -> Receipt {
+        require_admin(&env);
+        store_receipt(
+            &env,
+            deal_id,
+            ReceiptKind::TenantRepayment,
+            amount,
+            payer,
+            ReceiptDetails::none(),
+            0,
+            false,
+        )
+    }
+
+    /// Create a new receipt for a deal along with its payment metadata
+    ///
+    /// Mirrors the backend's `RecordReceiptParams`: the external reference and any
+    /// metadata are passed as SHA-256 hashes, and the FX conversion is recorded
+    /// when the payment was collected in NGN. Only `TenantRepayment` receipts
+    /// pay down the deal's schedule; unlike `create_receipt`, a repayment on a
+    /// deal that is not active, or for more than it owes, is rejected. On
+    /// NGN-denominated deals, repayments must carry `amount_ngn`, which is what
+    /// pays down their kobo schedule.
+    pub fn record_receipt(
+        env: Env,
+        deal_id: DealId,
+        kind: ReceiptKind,
+        amount: i128,
+        payer: Address,
+        details: ReceiptDetails,
+    ) -> Receipt {
+        require_admin(&env);
+
+        if kind == ReceiptKind::EarlySettlement {
+            panic!("early settlements are recorded by settle_early");
+        }
+        match (details.amount_ngn, details.fx_rate, &details.fx_provider) {
+            (None, None, None) => {}
+            (Some(amount_ngn), Some(fx_rate), Some(_)) => {
+                if amount_ngn <= 0 || fx_rate <= 0 {
+                    panic!("fx amount and rate must be positive");
+                }
+            }
+            _ => panic!("amount_ngn, fx_rate and fx_provider must be set together"),
+        }
+
+        store_receipt(&env, deal_id, kind, amount, payer, details, 0, true)
+    }
+
+    /// List receipts for a deal with cursor-based pagination
+    /// 
+    /// # Arguments
+    /// * `deal_id` - The deal ID to list receipts for
+    /// * `limit` - Maximum number of receipts to return (must be > 0 and <= 100)
+    /// * `cursor` - Optional cursor for pagination. If None, starts from the beginning.
+    ///              Cursor format: (timestamp, tx_id) tuple
+    /// 
+    /// # Returns
+    /// * `ReceiptPage` containing:
+    ///   - `receipts`: Vec of receipts ordered by (timestamp ASC, tx_id ASC)
+    ///   - `next_cursor`: Optional cursor for the next page, None if this is the last page
+    /// 
+    /// # Ordering
+    /// Receipts are ordered by:
+    /// 1. `timestamp` (ascending)
+    /// 2. `tx_id` (ascending, as bytes comparison)
+    /// 
+    /// This ensures stable, deterministic ordering even if multiple receipts have the same timestamp.
+    pub fn list_receipts_by_deal(
+        env: Env,
+        deal_id: DealId,
+        limit: u32,
+        cursor: Option<Cursor>,
+    ) -> ReceiptPage {
+        paginate_receipts(&env, get_receipts(&env, deal_id), limit, cursor, &ReceiptQuery::all())
+    }
+
+    /// List receipts for a deal within a time range and/or newest first
+    ///
+    /// Receipts keep the `(timestamp, tx_id)` ordering of `list_receipts_by_deal`,
+    /// reversed when `query.descending` is set. `next_cursor` continues in the
+    /// same direction; passing `prev_cursor` with `descending` flipped returns the
+    /// receipts preceding this page (nearest first).
+    pub fn query_receipts_by_deal(
+        env: Env,
+        deal_id: DealId,
+        limit: u32,
+        cursor: Option<Cursor>,
+        query: ReceiptQuery,
+    ) -> ReceiptPage {
+        paginate_receipts(&env, get_receipts(&env, deal_id), limit, cursor, &query)
+    }
+
+    /// List receipts paid by `payer` across all deals with cursor-based pagination
+    ///
+    /// Uses the same `(timestamp, tx_id)` ordering and `Cursor`/`ReceiptPage`
+    /// semantics as `list_receipts_by_deal`; `limit` must be between 1 and 100.
+    pub fn list_receipts_by_payer(
+        env: Env,
+        payer: Address,
+        limit: u32,
+        cursor: Option<Cursor>,
+    ) -> ReceiptPage {
+        paginate_payer_receipts(&env, &payer, limit, cursor, &ReceiptQuery::all())
+    }
+
+    /// List receipts paid by `payer` within a time range and/or newest first
+    ///
+    /// Same semantics as `query_receipts_by_deal`.
+    pub fn query_receipts_by_payer(
+        env: Env,
+        payer: Address,
+        limit: u32,
+        cursor: Option<Cursor>,
+        query: ReceiptQuery,
+    ) -> ReceiptPage {
+        paginate_payer_receipts(&env, &payer, limit, cursor, &query)
+    }
+
+    /// Reverse a receipt, e.g. after a card chargeback or refund
+    ///
+    /// History is never rewritten: a new entry with the negated amount is
+    /// appended to the deal, linked to the original through `reverses` /
+    /// `reversed_by`, and the deal totals and schedule are adjusted. A protocol
+    /// fee on the original is negated on the entry and taken off
+    /// `fees_collected`. `tx_id` identifies the refund/chargeback transaction
+    /// and must not already be recorded, so a retried call cannot reverse twice.
+    pub fn reverse_receipt(
+        env: Env,
+        deal_id: DealId,
+        receipt_id: ReceiptId,
+        reason: Symbol,
+        tx_id: TxId,
+    ) -> Receipt {
+        require_admin(&env);
+
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::ReceiptByTxId(tx_id.clone()))
+        {
+            panic!("tx_id already recorded");
+        }
+
+        let mut receipts = get_receipts(&env, deal_id);
+        let mut totals = get_deal_totals(&env, deal_id);
+        if receipt_id == 0 || receipt_id > receipts.len() as u64 {
+            panic!("receipt not found");
+        }
+        let index = (receipt_id - 1) as u32;
+        let mut original = receipts.get(index).unwrap();
+        if original.reverses.is_some() {
+            panic!("cannot reverse a reversal");
+        }
+        if original.reversed_by.is_some() {
+            panic!("receipt already reversed");
+        }
+        if original.kind == ReceiptKind::EarlySettlement {
+            panic!("cannot reverse an early settlement");
+        }
+
+        if original.kind == ReceiptKind::TenantRepayment {
+            // Version 1 receipts predate allocations and went entirely to the
+            // schedule; later ones undo only what they were allocated, which is
+            // nothing for payments the deal could not absorb
+            let allocations = &original.allocations;
+            let scheduled = if original.version == 1 {
+                original.amount
+            } else {
+                allocations.arrears + allocations.current + allocations.prepayment
+            };
+            let deal = get_deal(&env, deal_id).filter(|_| scheduled + allocations.fee > 0);
+            if let Some(mut deal) = deal {
+                let paid_by_period = env
+                    .storage()
+                    .persistent()
+                    .get(&DataKey::ReceiptInstallments(deal_id, receipt_id))
+                    .unwrap_or_else(|| Map::new(&env));
+                unapply_from_schedule(&env, &mut deal, scheduled, &paid_by_period);
+                if allocations.fee > 0 {
+                    put_fees_due(&env, deal_id, get_fees_due(&env, deal_id) + allocations.fee);
+                }
+                let paid = scheduled_amount(&env, deal_id, original.amount, original.amount_ngn);
+                let repaid = original.amount * (scheduled + allocations.fee) / paid;
+                update_tenant_record(&env, &deal.tenant, |record| {
+                    record.total_repaid -= repaid;
+                });
+            }
+        }
+
+        let reversal_id = increment_receipt_count(&env, deal_id);
+        let mut reversal = Receipt {
+            version: RECEIPT_VERSION,
+            id: reversal_id,
+            deal_id,
+            kind: original.kind,
+            amount: -original.amount,
+            timestamp: env.ledger().timestamp(),
+            tx_id,
+            payer: original.payer.clone(),
+            external_ref_hash: None,
+            token: original.token.clone(),
+            amount_ngn: original.amount_ngn.map(|amount_ngn| -amount_ngn),
+            fx_rate: original.fx_rate,
+            fx_provider: original.fx_provider.clone(),
+            from: original.to.clone(),
+            to: original.from.clone(),
+            metadata_hash: None,
+            reverses: Some(original.id),
+            reversed_by: None,
+            reversal_reason: Some(reason.clone()),
+            chain_hash: None,
+            leaf_index: None,
+            allocations: original.allocations.negated(),
+            protocol_fee: -original.protocol_fee,
+        };
+
+        reversal.leaf_index = Some(get_leaf_count(&env));
+        reversal.chain_hash = Some(extend_chain(&env, &reversal));
+        append_leaf(&env, &reversal);
+
+        original.reversed_by = Some(reversal_id);
+        receipts.set(index, original.clone());
+        receipts.push_back(reversal.clone());
+        put_receipts(&env, deal_id, receipts);
+        index_receipt(&env, &reversal);
+
+        add_to_totals(&mut totals, &reversal);
+        put_deal_totals(&env, deal_id, &totals);
+
+        if let (Some(token), true) = (&original.token, original.protocol_fee > 0) {
+            let mut collected = get_fees_collected(&env);
+            let fees = collected.get(token.clone()).unwrap_or(0);
+            collected.set(token.clone(), fees - original.protocol_fee);
+            env.storage().instance().set(&DataKey::FeesCollected, &collected);
+        }
+
+        env.events().publish(
+            (Symbol::new(&env, "receipt_reversed"), deal_id),
+            (receipt_id, reversal_id, original.amount, reason),
+        );
+
+        reversal
+    }
+
+    /// Current head of a deal's receipt hash chain (32 zero bytes before the
+    /// first receipt)
+    ///
+    /// Starting from zero, each receipt in id order extends the chain with
+    /// `sha256(head || xdr(receipt))`, where the receipt is taken with
+    /// `reversed_by` and `chain_hash` cleared. An off-chain verifier can replay
+    /// a deal's history and compare both each receipt's `chain_hash` and this
+    /// head; receipts upgraded from version 1 predate the chain and have no
+    /// `chain_hash`.
+    pub fn receipt_chain_head(env: Env, deal_id: DealId) -> BytesN<32> {
+        get_chain_head(&env, deal_id)
+    }
+
+    /// Root of the Merkle mountain range over every receipt recorded by the
+    /// contract, across all deals
+    pub fn receipts_root(env: Env) -> BytesN<32> {
+        bag_peaks(&env, get_leaf_count(&env), &get_peaks(&env))
+    }
+
+    /// Root of the accumulator when it held `leaf_count` receipts; `None` for
+    /// counts not reached yet, or reached before roots were kept per count
+    pub fn receipts_root_at(env: Env, leaf_count: u64) -> Option<BytesN<32>> {
+        get_root_at(&env, leaf_count)
+    }
+
+    /// Check that `receipt` is included in the accumulator as it stood at
+    /// `proof.leaf_count` receipts
+    ///
+    /// The receipt is hashed as recorded (see `receipt_chain_head`) at its
+    /// `leaf_index`. Proofs stay valid as more receipts are appended, since they
+    /// are checked against the root kept for their own leaf count.
+    pub fn verify_receipt_proof(env: Env, receipt: Receipt, proof: ReceiptProof) -> bool {
+        let leaf_count = proof.leaf_count;
+        let Some(root) = get_root_at(&env, leaf_count) else {
+            return false;
+        };
+        let Some(leaf_index) = receipt.leaf_index else {
+            return false;
+        };
+        let Some((position, height, local_index)) = locate_leaf(leaf_index, leaf_count) else {
+            return false;
+        };
+        if proof.siblings.len() != height || proof.peaks.len() != leaf_count.count_ones() {
+            return false;
+        }
+
+        let mut node = hash_leaf(&env, &receipt);
+        for (level, sibling) in proof.siblings.iter().enumerate() {
+            node = if (local_index >> level) & 1 == 0 {
+                hash_node(&env, &node, &sibling)
+            } else {
+                hash_node(&env, &sibling, &node)
+            };
+        }
+
+        proof.peaks.get(position) == Some(node) && bag_peaks(&env, leaf_count, &proof.peaks) == root
+    }
+
+    /// Account statement for a deal over `[period_start, period_end]`
+    /// (inclusive), computed only from stored fees and receipts
+    pub fn statement(env: Env, deal_id: DealId, period_start: Timestamp, period_end: Timestamp) -> Statement {
+        if period_start > period_end {
+            panic!("period_start must not be after period_end");
+        }
+        let deal = get_deal(&env, deal_id).expect("deal not found");
+        build_statement(&env, &deal, period_start, period_end)
+    }
+
+    /// Statements for every deal of a tenant, in the order the deals were created
+    pub fn tenant_statement(
+        env: Env,
+        tenant: Address,
+        period_start: Timestamp,
+        period_end: Timestamp,
+    ) -> Vec<Statement> {
+        if period_start > period_end {
+            panic!("period_start must not be after period_end");
+        }
+        let mut statements = vec![&env];
+        for deal_id in get_tenant_deals(&env, &tenant).iter() {
+            let deal = get_deal(&env, deal_id).unwrap();
+            statements.push_back(build_statement(&env, &deal, period_start, period_end));
+        }
+        statements
+    }
+
+    /// Get the total number of receipts for a deal
+    pub fn receipt_count(env: Env, deal_id: DealId) -> u64 {
+        get_receipt_count(&env, deal_id)
+    }
+
+    /// Find a receipt by the transaction ID it was recorded under
+    pub fn get_receipt_by_tx_id(env: Env, tx_id: TxId) -> Option<Receipt> {
+        env.storage()
+            .persistent()
+            .get::<_, ReceiptRef>(&DataKey::ReceiptByTxId(tx_id))
+            .and_then(|receipt_ref| get_receipt(&env, &receipt_ref))
+    }
+
+    /// Find a receipt by the SHA-256 hash of its canonical external reference
+    /// (e.g. `sha256("paystack:ref_123")`)
+    pub fn get_receipt_by_external_ref(env: Env, external_ref_hash: BytesN<32>) -> Option<Receipt> {
+        env.storage()
+            .persistent()
+            .get::<_, ReceiptRef>(&DataKey::ReceiptByExternalRef(external_ref_hash))
+            .and_then(|receipt_ref| get_receipt(&env, &receipt_ref))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use soroban_sdk::{
+        testutils::{Address as _, Events, Ledger, MockAuth, MockAuthInvoke},
+        Address, BytesN, Env, IntoVal, TryIntoVal,
+    };
+
+    #[allow(deprecated)]
+    fn setup(env: &Env) -> (Address, RentPaymentsClient<'_>, soroban_sdk::Address) {
+        let contract_id = env.register_contract(None, RentPayments);
+        // Note: register_contract is deprecated but still works in SDK 22.0.7
+        let client = RentPaymentsClient::new(env, &contract_id);
+        let admin = Address::generate(env);
+        client.init(&admin);
+        (admin, client, contract_id)
+    }
+
+
+    #[test]
+    fn test_list_receipts_by_deal_empty() {
+        let env = Env::default();
+        let (_admin, client, _contract_id) = setup(&env);
+        let deal_id = 1u64;
+
+        let page = client.list_receipts_by_deal(&deal_id, &10u32, &None);
+        assert_eq!(page.receipts.len(), 0);
+        assert!(!page.has_next);
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn test_list_receipts_by_deal_single_page() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let deal_id = 1u64;
+        let payer = Address::generate(&env);
+
+        // Create 5 receipts
+        for i in 1..=5 {
+            env.mock_auths(&[MockAuth {
+                address: &admin,
+                invoke: &MockAuthInvoke {
+                    contract: &contract_id,
+                    fn_name: "create_receipt",
+                    args: (deal_id, (i * 1000) as i128, payer.clone()).into_val(&env),
+                    sub_invokes: &[],
+                },
+            }]);
+            client.create_receipt(&deal_id, &(i * 1000), &payer);
+        }
+
+        let page = client.list_receipts_by_deal(&deal_id, &10u32, &None);
+        assert_eq!(page.receipts.len(), 5);
+        assert!(!page.has_next);
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn test_list_receipts_by_deal_pagination() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let deal_id = 1u64;
+        let payer = Address::generate(&env);
+
+        // Create 15 receipts
+        for i in 1..=15 {
+            env.mock_auths(&[MockAuth {
+                address: &admin,
+                invoke: &MockAuthInvoke {
+                    contract: &contract_id,
+                    fn_name: "create_receipt",
+                    args: (deal_id, (i * 1000) as i128, payer.clone()).into_val(&env),
+                    sub_invokes: &[],
+                },
+            }]);
+            client.create_receipt(&deal_id, &(i * 1000), &payer);
+        }
+
+        // First page: 10 receipts
+        let page1 = client.list_receipts_by_deal(&deal_id, &10u32, &None);
+        assert_eq!(page1.receipts.len(), 10);
+        assert!(page1.has_next);
+
+        // Second page: remaining 5 receipts
+        let cursor1 = page1.next_cursor.clone();
+        let page2 = client.list_receipts_by_deal(&deal_id, &10u32, &Some(cursor1));
+        assert_eq!(page2.receipts.len(), 5);
+        assert!(!page2.has_next);
+
+        // Verify no duplicates between pages
+        let page1_ids: std::vec::Vec<u64> = page1
+            .receipts
+            .iter()
+            .map(|r| r.id)
+            .collect();
+        let page2_ids: std::vec::Vec<u64> = page2
+            .receipts
+            .iter()
+            .map(|r| r.id)
+            .collect();
+
+        for id in &page1_ids {
+            assert!(!page2_ids.contains(id), "Duplicate receipt found: {}", id);
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn test_list_receipts_by_deal_no_skipping() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let deal_id = 1u64;
+        let payer = Address::generate(&env);
+
+        // Create 25 receipts
+        for i in 1..=25 {
+            env.mock_auths(&[MockAuth {
+                address: &admin,
+                invoke: &MockAuthInvoke {
+                    contract: &contract_id,
+                    fn_name: "create_receipt",
+                    args: (deal_id, (i * 1000) as i128, payer.clone()).into_val(&env),
+                    sub_invokes: &[],
+                },
+            }]);
+            client.create_receipt(&deal_id, &(i * 1000), &payer);
+        }
+
+        // Collect all receipts across pages
+        let mut all_receipt_ids = std::vec::Vec::new();
+        let mut cursor = None;
+
+        loop {
+            let page = client.list_receipts_by_deal(&deal_id, &10u32, &cursor);
+
+            for receipt in page.receipts.iter() {
+                all_receipt_ids.push(receipt.id);
+            }
+
+            if !page.has_next {
+                break;
+            }
+            cursor = Some(page.next_cursor.clone());
+        }
+
+        // Verify we got exactly 25 receipts (no skipping)
+        assert_eq!(all_receipt_ids.len(), 25);
+        
+        // Verify all IDs are unique (no duplicates)
+        let mut sorted_ids = all_receipt_ids.clone();
+        sorted_ids.sort();
+        sorted_ids.dedup();
+        assert_eq!(sorted_ids.len(), 25, "Found duplicate receipts");
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn test_list_receipts_by_deal_stable_ordering() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let deal_id = 1u64;
+        let payer = Address::generate(&env);
+
+        // Create multiple receipts
+        for i in 1..=10 {
+            env.mock_auths(&[MockAuth {
+                address: &admin,
+                invoke: &MockAuthInvoke {
+                    contract: &contract_id,
+                    fn_name: "create_receipt",
+                    args: (deal_id, (i * 1000) as i128, payer.clone()).into_val(&env),
+                    sub_invokes: &[],
+                },
+            }]);
+            client.create_receipt(&deal_id, &(i * 1000), &payer);
+        }
+
+        // Get all receipts in one call
+        let all_page = client.list_receipts_by_deal(&deal_id, &100u32, &None);
+        
+        // Get receipts in pages and verify ordering is consistent
+        let mut cursor = None;
+        let mut prev_timestamp = 0u64;
+        let mut prev_tx_id: Option<BytesN<32>> = None;
+
+        loop {
+            let page = client.list_receipts_by_deal(&deal_id, &3u32, &cursor);
+            
+            for receipt in page.receipts.iter() {
+                // Verify ordering: timestamp should be >= previous
+                assert!(
+                    receipt.timestamp >= prev_timestamp,
+                    "Receipts not in ascending timestamp order"
+                );
+                
+                // If timestamp is equal, tx_id should be >= previous
+                if receipt.timestamp == prev_timestamp {
+                    if let Some(ref prev) = prev_tx_id {
+                        let receipt_array = receipt.tx_id.to_array();
+                        let prev_array = prev.to_array();
+                        assert!(
+                            receipt_array >= prev_array,
+                            "Receipts with same timestamp not in ascending tx_id order"
+                        );
+                    }
+                }
+                
+                prev_timestamp = receipt.timestamp;
+                prev_tx_id = Some(receipt.tx_id.clone());
+            }
+
+            if !page.has_next {
+                break;
+            }
+            cursor = Some(page.next_cursor.clone());
+        }
+
+        // Verify the full list matches the single-page result
+        let mut paginated_ids: std::vec::Vec<u64> = std::vec::Vec::new();
+        cursor = None;
+        loop {
+            let page = client.list_receipts_by_deal(&deal_id, &3u32, &cursor);
+            for receipt in page.receipts.iter() {
+                paginated_ids.push(receipt.id);
+            }
+            if !page.has_next {
+                break;
+            }
+            cursor = Some(page.next_cursor.clone());
+        }
+
+        let all_ids: std::vec::Vec<u64> = all_page.receipts.iter().map(|r| r.id).collect();
+        assert_eq!(paginated_ids, all_ids, "Paginated results don't match full results");
+    }
+
+    #[test]
+    #[should_panic(expected = "limit must be between 1 and 100")]
+    fn test_list_receipts_by_deal_invalid_limit_zero() {
+        let env = Env::default();
+        let (_admin, client, _contract_id) = setup(&env);
+        let deal_id = 1u64;
+
+        client.list_receipts_by_deal(&deal_id, &0u32, &None);
+    }
+
+    #[test]
+    #[should_panic(expected = "limit must be between 1 and 100")]
+    fn test_list_receipts_by_deal_invalid_limit_too_large() {
+        let env = Env::default();
+        let (_admin, client, _contract_id) = setup(&env);
+        let deal_id = 1u64;
+
+        client.list_receipts_by_deal(&deal_id, &101u32, &None);
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn test_list_receipts_by_deal_different_deals() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let payer = Address::generate(&env);
+
+        // Create receipts for deal 1
+        for i in 1..=5 {
+            env.mock_auths(&[MockAuth {
+                address: &admin,
+                invoke: &MockAuthInvoke {
+                    contract: &contract_id,
+                    fn_name: "create_receipt",
+                    args: (1u64, (i * 1000) as i128, payer.clone()).into_val(&env),
+                    sub_invokes: &[],
+                },
+            }]);
+            client.create_receipt(&1u64, &(i * 1000), &payer);
+        }
+
+        // Create receipts for deal 2
+        for i in 1..=3 {
+            env.mock_auths(&[MockAuth {
+                address: &admin,
+                invoke: &MockAuthInvoke {
+                    contract: &contract_id,
+                    fn_name: "create_receipt",
+                    args: (2u64, (i * 2000) as i128, payer.clone()).into_val(&env),
+                    sub_invokes: &[],
+                },
+            }]);
+            client.create_receipt(&2u64, &(i * 2000), &payer);
+        }
+
+        // Verify deal 1 has 5 receipts
+        let page1 = client.list_receipts_by_deal(&1u64, &10u32, &None);
+        assert_eq!(page1.receipts.len(), 5);
+
+        // Verify deal 2 has 3 receipts
+        let page2 = client.list_receipts_by_deal(&2u64, &10u32, &None);
+        assert_eq!(page2.receipts.len(), 3);
+    }
+
+    fn sample_details(env: &Env, payer: &Address) -> ReceiptDetails {
+        ReceiptDetails {
+            external_ref_hash: Some(BytesN::from_array(env, &[7u8; 32])),
+            token: Some(Address::generate(env)),
+            amount_ngn: Some(150_000_000),
+            fx_rate: Some(1_500 * FX_RATE_SCALE),
+            fx_provider: Some(Symbol::new(env, "paystack")),
+            from: Some(payer.clone()),
+            to: Some(Address::generate(env)),
+            metadata_hash: Some(BytesN::from_array(env, &[9u8; 32])),
+        }
+    }
+
+    #[test]
+    fn test_record_receipt_stores_details() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let deal_id = 1u64;
+        let payer = Address::generate(&env);
+        let details = sample_details(&env, &payer);
+
+        env.mock_auths(&[MockAuth {
+            address: &admin,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "record_receipt",
+                args: (deal_id, ReceiptKind::TenantRepayment, 1000i128, payer.clone(), details.clone())
+                    .into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+        let receipt =
+            client.record_receipt(&deal_id, &ReceiptKind::TenantRepayment, &1000i128, &payer, &details);
+
+        assert_eq!(receipt.version, RECEIPT_VERSION);
+        assert_eq!(receipt.external_ref_hash, details.external_ref_hash);
+        assert_eq!(receipt.token, details.token);
+        assert_eq!(receipt.amount_ngn, details.amount_ngn);
+        assert_eq!(receipt.fx_rate, details.fx_rate);
+        assert_eq!(receipt.fx_provider, details.fx_provider);
+        assert_eq!(receipt.from, details.from);
+        assert_eq!(receipt.to, details.to);
+        assert_eq!(receipt.metadata_hash, details.metadata_hash);
+
+        let page = client.list_receipts_by_deal(&deal_id, &10u32, &None);
+        assert_eq!(page.receipts.get(0).unwrap(), receipt);
+    }
+
+    #[test]
+    fn test_create_receipt_leaves_details_empty() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let payer = Address::generate(&env);
+
+        env.mock_auths(&[MockAuth {
+            address: &admin,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "create_receipt",
+                args: (1u64, 1000i128, payer.clone()).into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+        let receipt = client.create_receipt(&1u64, &1000i128, &payer);
+
+        assert_eq!(receipt.version, RECEIPT_VERSION);
+        assert_eq!(receipt.external_ref_hash, None);
+        assert_eq!(receipt.token, None);
+        assert_eq!(receipt.amount_ngn, None);
+        assert_eq!(receipt.fx_rate, None);
+        assert_eq!(receipt.fx_provider, None);
+        assert_eq!(receipt.metadata_hash, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "fx amount and rate must be positive")]
+    fn test_record_receipt_rejects_zero_fx_rate() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let payer = Address::generate(&env);
+        let mut details = sample_details(&env, &payer);
+        details.fx_rate = Some(0);
+
+        env.mock_auths(&[MockAuth {
+            address: &admin,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "record_receipt",
+                args: (1u64, ReceiptKind::TenantRepayment, 1000i128, payer.clone(), details.clone())
+                    .into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+        client.record_receipt(&1u64, &ReceiptKind::TenantRepayment, &1000i128, &payer, &details);
+    }
+
+    #[test]
+    #[should_panic(expected = "amount_ngn, fx_rate and fx_provider must be set together")]
+    fn test_record_receipt_rejects_partial_fx() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let payer = Address::generate(&env);
+        let mut details = sample_details(&env, &payer);
+        details.fx_provider = None;
+
+        env.mock_auths(&[MockAuth {
+            address: &admin,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "record_receipt",
+                args: (1u64, ReceiptKind::TenantRepayment, 1000i128, payer.clone(), details.clone())
+                    .into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+        client.record_receipt(&1u64, &ReceiptKind::TenantRepayment, &1000i128, &payer, &details);
+    }
+
+    #[test]
+    fn test_version_1_receipts_remain_readable() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let deal_id = 1u64;
+        let payer = Address::generate(&env);
+
+        // Simulate receipts written by a deployment predating receipt metadata
+        let legacy = ReceiptV1 {
+            id: 1,
+            deal_id,
+            amount: 500,
+            timestamp: 0,
+            tx_id: BytesN::from_array(&env, &[0u8; 32]),
+            payer: payer.clone(),
+        };
+        env.as_contract(&contract_id, || {
+            let storage = env.storage().persistent();
+            storage.set(&DataKey::Receipts(deal_id), &vec![&env, legacy.clone()]);
+            storage.set(&DataKey::ReceiptCount(deal_id), &1u64);
+        });
+
+        let page = client.list_receipts_by_deal(&deal_id, &10u32, &None);
+        assert_eq!(page.receipts.len(), 1);
+        let upgraded = page.receipts.get(0).unwrap();
+        assert_eq!(upgraded.version, 1);
+        assert_eq!(upgraded.amount, 500);
+        assert_eq!(upgraded.external_ref_hash, None);
+
+        // A new receipt carries the legacy history over into the current layout
+        env.mock_auths(&[MockAuth {
+            address: &admin,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "create_receipt",
+                args: (deal_id, 1000i128, payer.clone()).into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+        let receipt = client.create_receipt(&deal_id, &1000i128, &payer);
+        assert_eq!(receipt.id, 2);
+
+        let page = client.list_receipts_by_deal(&deal_id, &10u32, &None);
+        assert_eq!(page.receipts.len(), 2);
+        assert_eq!(page.receipts.get(0).unwrap(), upgraded);
+        env.as_contract(&contract_id, || {
+            assert!(!env.storage().persistent().has(&DataKey::Receipts(deal_id)));
+        });
+        assert_eq!(client.get_receipt_by_tx_id(&legacy.tx_id), Some(upgraded));
+    }
+
+    #[test]
+    fn test_get_receipt_by_tx_id() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let payer = Address::generate(&env);
+
+        let mut created = std::vec::Vec::new();
+        for deal_id in 1..=3u64 {
+            env.mock_auths(&[MockAuth {
+                address: &admin,
+                invoke: &MockAuthInvoke {
+                    contract: &contract_id,
+                    fn_name: "create_receipt",
+                    args: (deal_id, 1000i128, payer.clone()).into_val(&env),
+                    sub_invokes: &[],
+                },
+            }]);
+            created.push(client.create_receipt(&deal_id, &1000i128, &payer));
+        }
+
+        for receipt in created.iter() {
+            assert_eq!(client.get_receipt_by_tx_id(&receipt.tx_id), Some(receipt.clone()));
+        }
+        let unknown = BytesN::from_array(&env, &[0xAAu8; 32]);
+        assert_eq!(client.get_receipt_by_tx_id(&unknown), None);
+    }
+
+    #[test]
+    fn test_get_receipt_by_external_ref() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let payer = Address::generate(&env);
+        let details = sample_details(&env, &payer);
+
+        env.mock_auths(&[MockAuth {
+            address: &admin,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "record_receipt",
+                args: (7u64, ReceiptKind::TenantRepayment, 2500i128, payer.clone(), details.clone())
+                    .into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+        let receipt =
+            client.record_receipt(&7u64, &ReceiptKind::TenantRepayment, &2500i128, &payer, &details);
+
+        let hash = details.external_ref_hash.unwrap();
+        assert_eq!(client.get_receipt_by_external_ref(&hash), Some(receipt));
+        let unknown = BytesN::from_array(&env, &[0xAAu8; 32]);
+        assert_eq!(client.get_receipt_by_external_ref(&unknown), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "external reference already recorded")]
+    fn test_record_receipt_rejects_duplicate_external_ref() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let payer = Address::generate(&env);
+        let details = sample_details(&env, &payer);
+
+        for deal_id in [1u64, 2u64] {
+            env.mock_auths(&[MockAuth {
+                address: &admin,
+                invoke: &MockAuthInvoke {
+                    contract: &contract_id,
+                    fn_name: "record_receipt",
+                    args: (deal_id, ReceiptKind::TenantRepayment, 2500i128, payer.clone(), details.clone())
+                        .into_val(&env),
+                    sub_invokes: &[],
+                },
+            }]);
+            client.record_receipt(
+                &deal_id,
+                &ReceiptKind::TenantRepayment,
+                &2500i128,
+                &payer,
+                &details,
+            );
+        }
+    }
+
+    #[test]
+    fn test_list_receipts_by_payer_across_deals() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        let co_tenant = Address::generate(&env);
+
+        // The tenant pays two deals; a co-tenant also pays the first deal
+        for (deal_id, payer) in [
+            (1u64, tenant.clone()),
+            (2u64, tenant.clone()),
+            (1u64, co_tenant.clone()),
+            (2u64, tenant.clone()),
+            (1u64, tenant.clone()),
+        ] {
+            env.mock_auths(&[MockAuth {
+                address: &admin,
+                invoke: &MockAuthInvoke {
+                    contract: &contract_id,
+                    fn_name: "create_receipt",
+                    args: (deal_id, 1000i128, payer.clone()).into_val(&env),
+                    sub_invokes: &[],
+                },
+            }]);
+            client.create_receipt(&deal_id, &1000i128, &payer);
+        }
+
+        let page = client.list_receipts_by_payer(&tenant, &10u32, &None);
+        assert_eq!(page.receipts.len(), 4);
+        assert!(!page.has_next);
+        assert!(page.receipts.iter().all(|r| r.payer == tenant));
+        let deals: std::vec::Vec<u64> = page.receipts.iter().map(|r| r.deal_id).collect();
+        assert_eq!(deals, std::vec![1, 2, 2, 1]);
+
+        let page = client.list_receipts_by_payer(&co_tenant, &10u32, &None);
+        assert_eq!(page.receipts.len(), 1);
+        assert_eq!(page.receipts.get(0).unwrap().deal_id, 1);
+
+        let stranger = Address::generate(&env);
+        let page = client.list_receipts_by_payer(&stranger, &10u32, &None);
+        assert_eq!(page.receipts.len(), 0);
+        assert!(!page.has_next);
+    }
+
+    #[test]
+    fn test_list_receipts_by_payer_pagination() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let payer = Address::generate(&env);
+
+        for i in 1..=12u64 {
+            let deal_id = i % 3;
+            env.mock_auths(&[MockAuth {
+                address: &admin,
+                invoke: &MockAuthInvoke {
+                    contract: &contract_id,
+                    fn_name: "create_receipt",
+                    args: (deal_id, 1000i128, payer.clone()).into_val(&env),
+                    sub_invokes: &[],
+                },
+            }]);
+            client.create_receipt(&deal_id, &1000i128, &payer);
+        }
+
+        let mut seen = std::vec::Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = client.list_receipts_by_payer(&payer, &5u32, &cursor);
+            for r in page.receipts.iter() {
+                seen.push(r.tx_id.to_array());
+            }
+            if !page.has_next {
+                break;
+            }
+            cursor = Some(page.next_cursor.clone());
+        }
+
+        assert_eq!(seen.len(), 12);
+        let mut sorted = seen.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(sorted.len(), 12, "Found duplicate receipts");
+    }
+
+    #[test]
+    #[should_panic(expected = "limit must be between 1 and 100")]
+    fn test_list_receipts_by_payer_invalid_limit() {
+        let env = Env::default();
+        let (_admin, client, _contract_id) = setup(&env);
+        let payer = Address::generate(&env);
+
+        client.list_receipts_by_payer(&payer, &0u32, &None);
+    }
+
+    /// Create a receipt with the ledger clock set to `timestamp`
+    fn create_receipt_at(
+        env: &Env,
+        client: &RentPaymentsClient<'_>,
+        contract_id: &Address,
+        admin: &Address,
+        deal_id: DealId,
+        payer: &Address,
+        timestamp: Timestamp,
+    ) -> Receipt {
+        env.ledger().set_timestamp(timestamp);
+        env.mock_auths(&[MockAuth {
+            address: admin,
+            invoke: &MockAuthInvoke {
+                contract: contract_id,
+                fn_name: "create_receipt",
+                args: (deal_id, 1000i128, payer.clone()).into_val(env),
+                sub_invokes: &[],
+            },
+        }]);
+        client.create_receipt(&deal_id, &1000i128, payer)
+    }
+
+    fn ids(page: &ReceiptPage) -> std::vec::Vec<u64> {
+        page.receipts.iter().map(|r| r.id).collect()
+    }
+
+    #[test]
+    fn test_query_receipts_by_deal_descending() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let payer = Address::generate(&env);
+        for i in 1..=10u64 {
+            create_receipt_at(&env, &client, &contract_id, &admin, 1, &payer, i * 100);
+        }
+
+        let newest_first = ReceiptQuery {
+            from_timestamp: None,
+            to_timestamp: None,
+            descending: true,
+        };
+        let page1 = client.query_receipts_by_deal(&1u64, &3u32, &None, &newest_first);
+        assert_eq!(ids(&page1), std::vec![10, 9, 8]);
+        assert!(page1.has_next);
+        assert!(!page1.has_prev);
+
+        let page2 =
+            client.query_receipts_by_deal(&1u64, &3u32, &Some(page1.next_cursor), &newest_first);
+        assert_eq!(ids(&page2), std::vec![7, 6, 5]);
+        assert!(page2.has_prev);
+    }
+
+    #[test]
+    fn test_query_receipts_by_deal_time_range() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let payer = Address::generate(&env);
+        for i in 1..=10u64 {
+            create_receipt_at(&env, &client, &contract_id, &admin, 1, &payer, i * 100);
+        }
+
+        let range = ReceiptQuery {
+            from_timestamp: Some(300),
+            to_timestamp: Some(600),
+            descending: false,
+        };
+        let page1 = client.query_receipts_by_deal(&1u64, &3u32, &None, &range);
+        assert_eq!(ids(&page1), std::vec![3, 4, 5]);
+        assert!(page1.has_next);
+
+        let page2 = client.query_receipts_by_deal(&1u64, &3u32, &Some(page1.next_cursor), &range);
+        assert_eq!(ids(&page2), std::vec![6]);
+        assert!(!page2.has_next);
+
+        let open_ended = ReceiptQuery {
+            from_timestamp: Some(901),
+            to_timestamp: None,
+            descending: false,
+        };
+        let page = client.query_receipts_by_deal(&1u64, &10u32, &None, &open_ended);
+        assert_eq!(ids(&page), std::vec![10]);
+    }
+
+    #[test]
+    fn test_query_receipts_by_deal_prev_cursor() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let payer = Address::generate(&env);
+        for i in 1..=9u64 {
+            create_receipt_at(&env, &client, &contract_id, &admin, 1, &payer, i * 100);
+        }
+
+        let oldest_first = ReceiptQuery::all();
+        let page1 = client.query_receipts_by_deal(&1u64, &3u32, &None, &oldest_first);
+        let page2 =
+            client.query_receipts_by_deal(&1u64, &3u32, &Some(page1.next_cursor), &oldest_first);
+        assert_eq!(ids(&page2), std::vec![4, 5, 6]);
+        assert!(page2.has_prev);
+
+        // Walking back from page 2 returns page 1, nearest first
+        let newest_first = ReceiptQuery {
+            descending: true,
+            ..ReceiptQuery::all()
+        };
+        let back =
+            client.query_receipts_by_deal(&1u64, &3u32, &Some(page2.prev_cursor), &newest_first);
+        assert_eq!(ids(&back), std::vec![3, 2, 1]);
+        assert!(!back.has_next);
+        assert!(back.has_prev);
+    }
+
+    #[test]
+    fn test_query_receipts_by_payer_descending() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let payer = Address::generate(&env);
+        for i in 1..=4u64 {
+            create_receipt_at(&env, &client, &contract_id, &admin, i, &payer, i * 100);
+        }
+
+        let newest_first = ReceiptQuery {
+            descending: true,
+            ..ReceiptQuery::all()
+        };
+        let page = client.query_receipts_by_payer(&payer, &2u32, &None, &newest_first);
+        let deals: std::vec::Vec<u64> = page.receipts.iter().map(|r| r.deal_id).collect();
+        assert_eq!(deals, std::vec![4, 3]);
+        assert!(page.has_next);
+    }
+
+    #[test]
+    fn test_payer_index_pages_like_deal_receipts() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let payer = Address::generate(&env);
+        // Several receipts share a timestamp, so ties fall back to tx_id
+        for ts in [100u64, 100, 200, 300, 300, 300, 400, 500, 500] {
+            create_receipt_at(&env, &client, &contract_id, &admin, 1, &payer, ts);
+        }
+
+        // A payer whose receipts are all on one deal pages exactly like that deal
+        for (from, to) in [(None, None), (Some(100), Some(300)), (Some(250), None), (None, Some(450))] {
+            for descending in [false, true] {
+                let query = ReceiptQuery {
+                    from_timestamp: from,
+                    to_timestamp: to,
+                    descending,
+                };
+                let mut cursor = None;
+                loop {
+                    let by_payer = client.query_receipts_by_payer(&payer, &2u32, &cursor, &query);
+                    let by_deal = client.query_receipts_by_deal(&1u64, &2u32, &cursor, &query);
+                    assert_eq!(by_payer.receipts, by_deal.receipts);
+                    assert_eq!(by_payer.has_next, by_deal.has_next);
+                    assert_eq!(by_payer.has_prev, by_deal.has_prev);
+                    assert_eq!(by_payer.next_cursor, by_deal.next_cursor);
+                    assert_eq!(by_payer.prev_cursor, by_deal.prev_cursor);
+                    if !by_payer.has_next {
+                        break;
+                    }
+                    cursor = Some(by_payer.next_cursor);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "from_timestamp must not be after to_timestamp")]
+    fn test_query_receipts_by_deal_invalid_range() {
+        let env = Env::default();
+        let (_admin, client, _contract_id) = setup(&env);
+
+        let range = ReceiptQuery {
+            from_timestamp: Some(600),
+            to_timestamp: Some(300),
+            descending: false,
+        };
+        client.query_receipts_by_deal(&1u64, &10u32, &None, &range);
+    }
+
+    const DAY: u64 = 24 * 60 * 60;
+
+    /// Register a deal whose first installment is due at `first_due`
+    #[allow(clippy::too_many_arguments)]
+    fn create_deal_as_admin(
+        env: &Env,
+        client: &RentPaymentsClient<'_>,
+        contract_id: &Address,
+        admin: &Address,
+        deal_id: DealId,
+        tenant: &Address,
+        principal: i128,
+        term: u32,
+        first_due: Timestamp,
+    ) -> Deal {
+        let landlord = Address::generate(env);
+        env.mock_auths(&[MockAuth {
+            address: admin,
+            invoke: &MockAuthInvoke {
+                contract: contract_id,
+                fn_name: "create_deal",
+                args: (deal_id, tenant.clone(), landlord.clone(), principal, term, first_due)
+                    .into_val(env),
+                sub_invokes: &[],
+            },
+        }]);
+        client.create_deal(&deal_id, tenant, &landlord, &principal, &term, &first_due)
+    }
+
+    fn pay_as_admin(
+        env: &Env,
+        client: &RentPaymentsClient<'_>,
+        contract_id: &Address,
+        admin: &Address,
+        deal_id: DealId,
+        amount: i128,
+        payer: &Address,
+    ) -> Receipt {
+        env.mock_auths(&[MockAuth {
+            address: admin,
+            invoke: &MockAuthInvoke {
+                contract: contract_id,
+                fn_name: "create_receipt",
+                args: (deal_id, amount, payer.clone()).into_val(env),
+                sub_invokes: &[],
+            },
+        }]);
+        client.create_receipt(&deal_id, &amount, payer)
+    }
+
+    #[test]
+    fn test_create_deal_builds_schedule() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+
+        let deal = create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 1000, 3, 30 * DAY);
+        assert_eq!(deal.status, DealStatus::Active);
+        assert_eq!(client.get_deal(&1u64), deal);
+
+        let schedule = client.get_schedule(&1u64);
+        let amounts: std::vec::Vec<i128> = schedule.iter().map(|i| i.amount).collect();
+        assert_eq!(amounts, std::vec![333, 333, 334]);
+        let due_dates: std::vec::Vec<u64> = schedule.iter().map(|i| i.due_date).collect();
+        assert_eq!(due_dates, std::vec![30 * DAY, 60 * DAY, 90 * DAY]);
+    }
+
+    #[test]
+    #[should_panic(expected = "deal already exists")]
+    fn test_create_deal_rejects_duplicate() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 1000, 3, 30 * DAY);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 1000, 3, 30 * DAY);
+    }
+
+    #[test]
+    fn test_deal_summary_tracks_payments() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+
+        env.ledger().set_timestamp(10 * DAY);
+        pay_as_admin(&env, &client, &contract_id, &admin, 1, 400, &tenant);
+
+        let landlord_payout = ReceiptDetails::none();
+        env.mock_auths(&[MockAuth {
+            address: &admin,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "record_receipt",
+                args: (1u64, ReceiptKind::LandlordPayout, 5000i128, admin.clone(), landlord_payout.clone())
+                    .into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+        client.record_receipt(&1u64, &ReceiptKind::LandlordPayout, &5000i128, &admin, &landlord_payout);
+
+        let summary = client.deal_summary(&1u64);
+        assert!(summary.registered);
+        assert_eq!(summary.status, DealStatus::Active);
+        assert_eq!(summary.total_paid, 400);
+        assert_eq!(summary.receipt_count, 2);
+        assert_eq!(summary.last_payment_at, Some(10 * DAY));
+        assert_eq!(summary.by_kind.get(ReceiptKind::TenantRepayment), Some(400));
+        assert_eq!(summary.by_kind.get(ReceiptKind::LandlordPayout), Some(5000));
+        assert_eq!(summary.outstanding, 500);
+        assert_eq!(summary.installments_paid, 1);
+        assert_eq!(summary.next_due_date, Some(60 * DAY));
+        assert_eq!(summary.next_due_amount, 200);
+        assert_eq!(summary.installments_late, 0);
+    }
+
+    #[test]
+    fn test_deal_summary_counts_late_installments() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+
+        // Within the grace period the first installment is due, not late
+        env.ledger().set_timestamp(30 * DAY + GRACE_PERIOD);
+        assert_eq!(client.deal_summary(&1u64).installments_late, 0);
+
+        env.ledger().set_timestamp(60 * DAY + GRACE_PERIOD + 1);
+        let summary = client.deal_summary(&1u64);
+        assert_eq!(summary.installments_late, 2);
+        assert_eq!(summary.next_due_date, Some(30 * DAY));
+    }
+
+    #[test]
+    fn test_deal_completes_when_fully_paid() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+
+        pay_as_admin(&env, &client, &contract_id, &admin, 1, 600, &tenant);
+        pay_as_admin(&env, &client, &contract_id, &admin, 1, 300, &tenant);
+
+        let summary = client.deal_summary(&1u64);
+        assert_eq!(summary.status, DealStatus::Completed);
+        assert_eq!(summary.outstanding, 0);
+        assert_eq!(summary.installments_paid, 3);
+        assert_eq!(summary.next_due_date, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "amount exceeds outstanding balance")]
+    fn test_repayment_cannot_exceed_outstanding() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+
+        env.mock_all_auths();
+        let kind = ReceiptKind::TenantRepayment;
+        client.record_receipt(&1u64, &kind, &901i128, &tenant, &ReceiptDetails::none());
+    }
+
+    #[test]
+    fn test_create_receipt_records_what_the_deal_cannot_absorb() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+
+        // Only what the deal owes is applied; the excess is still recorded
+        let receipt = pay_as_admin(&env, &client, &contract_id, &admin, 1, 1000, &tenant);
+        let a = &receipt.allocations;
+        assert_eq!(a.fee + a.arrears + a.current + a.prepayment, 900);
+        assert_eq!(receipt.amount, 1000);
+        assert_eq!(client.get_deal(&1u64).status, DealStatus::Completed);
+
+        // A completed deal still accepts receipts, with nothing allocated
+        let receipt = pay_as_admin(&env, &client, &contract_id, &admin, 1, 50, &tenant);
+        assert_eq!(receipt.allocations, Allocation::none());
+        assert_eq!(client.deal_summary(&1u64).total_paid, 1050);
+    }
+
+    #[test]
+    #[should_panic(expected = "term exceeds MAX_TERM")]
+    fn test_create_deal_rejects_term_over_max() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, MAX_TERM + 1, 30 * DAY);
+    }
+
+    #[test]
+    #[should_panic(expected = "due date out of range")]
+    fn test_create_deal_rejects_due_dates_past_max_timestamp() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, u64::MAX - DAY);
+    }
+
+    #[test]
+    fn test_deal_summary_without_registered_deal() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let payer = Address::generate(&env);
+
+        pay_as_admin(&env, &client, &contract_id, &admin, 5, 250, &payer);
+        pay_as_admin(&env, &client, &contract_id, &admin, 5, 750, &payer);
+
+        let summary = client.deal_summary(&5u64);
+        assert!(!summary.registered);
+        assert_eq!(summary.total_paid, 1000);
+        assert_eq!(summary.receipt_count, 2);
+        assert_eq!(summary.outstanding, 0);
+        assert_eq!(summary.next_due_date, None);
+    }
+
+    fn reverse_as_admin(
+        env: &Env,
+        client: &RentPaymentsClient<'_>,
+        contract_id: &Address,
+        admin: &Address,
+        deal_id: DealId,
+        receipt_id: ReceiptId,
+        tx_byte: u8,
+    ) -> Receipt {
+        let reason = Symbol::new(env, "chargeback");
+        let tx_id = BytesN::from_array(env, &[tx_byte; 32]);
+        env.mock_auths(&[MockAuth {
+            address: admin,
+            invoke: &MockAuthInvoke {
+                contract: contract_id,
+                fn_name: "reverse_receipt",
+                args: (deal_id, receipt_id, reason.clone(), tx_id.clone()).into_val(env),
+                sub_invokes: &[],
+            },
+        }]);
+        client.reverse_receipt(&deal_id, &receipt_id, &reason, &tx_id)
+    }
+
+    #[test]
+    fn test_reverse_receipt_appends_linked_entry() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+        pay_as_admin(&env, &client, &contract_id, &admin, 1, 300, &tenant);
+        let original = pay_as_admin(&env, &client, &contract_id, &admin, 1, 200, &tenant);
+
+        let reversal = reverse_as_admin(&env, &client, &contract_id, &admin, 1, original.id, 0xCC);
+        assert_eq!(reversal.id, 3);
+        assert_eq!(reversal.amount, -200);
+        assert_eq!(reversal.reverses, Some(original.id));
+        assert_eq!(reversal.reversal_reason, Some(Symbol::new(&env, "chargeback")));
+
+        // The original stays in history, marked as reversed
+        let page = client.list_receipts_by_deal(&1u64, &10u32, &None);
+        assert_eq!(page.receipts.len(), 3);
+        let stored = client.get_receipt_by_tx_id(&original.tx_id).unwrap();
+        assert_eq!(stored.amount, 200);
+        assert_eq!(stored.reversed_by, Some(reversal.id));
+        assert_eq!(client.get_receipt_by_tx_id(&reversal.tx_id), Some(reversal));
+
+        let summary = client.deal_summary(&1u64);
+        assert_eq!(summary.total_paid, 300);
+        assert_eq!(summary.receipt_count, 3);
+        assert_eq!(summary.outstanding, 600);
+        let schedule = client.get_schedule(&1u64);
+        assert_eq!(schedule.get(0).unwrap().paid, 300);
+        assert_eq!(schedule.get(1).unwrap().paid, 0);
+    }
+
+    #[test]
+    fn test_reverse_receipt_reopens_completed_deal() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+        let receipt = pay_as_admin(&env, &client, &contract_id, &admin, 1, 900, &tenant);
+        assert_eq!(client.get_deal(&1u64).status, DealStatus::Completed);
+
+        reverse_as_admin(&env, &client, &contract_id, &admin, 1, receipt.id, 0xCC);
+
+        let summary = client.deal_summary(&1u64);
+        assert_eq!(summary.status, DealStatus::Active);
+        assert_eq!(summary.outstanding, 900);
+        assert_eq!(summary.installments_paid, 0);
+    }
+
+    #[test]
+    fn test_reverse_receipt_leaves_schedule_for_unallocated_payment() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+        pay_as_admin(&env, &client, &contract_id, &admin, 1, 900, &tenant);
+        // The deal is completed, so this payment is recorded without an allocation
+        let excess = pay_as_admin(&env, &client, &contract_id, &admin, 1, 50, &tenant);
+        assert_eq!(excess.allocations, Allocation::none());
+
+        reverse_as_admin(&env, &client, &contract_id, &admin, 1, excess.id, 0xCC);
+
+        let summary = client.deal_summary(&1u64);
+        assert_eq!(summary.status, DealStatus::Completed);
+        assert_eq!(summary.outstanding, 0);
+        assert_eq!(summary.installments_paid, 3);
+        assert_eq!(client.total_outstanding(), 0);
+        let record = client.tenant_record(&tenant);
+        assert_eq!((record.deals_completed, record.installments_on_time), (1, 3));
+        assert_eq!(record.total_repaid, 900);
+    }
+
+    #[test]
+    fn test_reverse_receipt_undoes_the_installments_it_paid() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+        let on_time = pay_as_admin(&env, &client, &contract_id, &admin, 1, 300, &tenant);
+        env.ledger().set_timestamp(100 * DAY);
+        pay_as_admin(&env, &client, &contract_id, &admin, 1, 300, &tenant);
+        let record = client.tenant_record(&tenant);
+        assert_eq!((record.installments_on_time, record.installments_late), (1, 1));
+
+        // The first receipt paid period 1, so the late period 2 stays paid
+        reverse_as_admin(&env, &client, &contract_id, &admin, 1, on_time.id, 0xCC);
+        let schedule = client.get_schedule(&1u64);
+        assert_eq!(schedule.get(0).unwrap().paid, 0);
+        assert_eq!(schedule.get(1).unwrap().paid, 300);
+        let record = client.tenant_record(&tenant);
+        assert_eq!((record.installments_on_time, record.installments_late), (0, 1));
+    }
+
+    #[test]
+    fn test_reverse_receipt_on_defaulted_deal_leaves_total_outstanding() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 2, &tenant, 600, 2, 30 * DAY);
+        let receipt = pay_as_admin(&env, &client, &contract_id, &admin, 1, 300, &tenant);
+        env.mock_all_auths();
+        client.mark_default(&1u64);
+        assert_eq!(client.total_outstanding(), 600);
+
+        reverse_as_admin(&env, &client, &contract_id, &admin, 1, receipt.id, 0xCC);
+        assert_eq!(client.total_outstanding(), 600);
+        assert_eq!(client.deal_summary(&1u64).outstanding, 900);
+    }
+
+    #[test]
+    fn test_reverse_receipt_emits_event() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let payer = Address::generate(&env);
+        let receipt = pay_as_admin(&env, &client, &contract_id, &admin, 4, 500, &payer);
+
+        let reversal = reverse_as_admin(&env, &client, &contract_id, &admin, 4, receipt.id, 0xCC);
+
+        let events = env.events().all();
+        let event = events.last().unwrap();
+        let topics: soroban_sdk::Vec<soroban_sdk::Val> = event.1.clone();
+        let event_name: Symbol = topics.get(0).unwrap().try_into_val(&env).unwrap();
+        assert_eq!(event_name, Symbol::new(&env, "receipt_reversed"));
+        let event_deal: u64 = topics.get(1).unwrap().try_into_val(&env).unwrap();
+        assert_eq!(event_deal, 4);
+        let data: (u64, u64, i128, Symbol) = event.2.try_into_val(&env).unwrap();
+        assert_eq!(data, (receipt.id, reversal.id, 500, Symbol::new(&env, "chargeback")));
+    }
+
+    #[test]
+    #[should_panic(expected = "receipt already reversed")]
+    fn test_reverse_receipt_twice_fails() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let payer = Address::generate(&env);
+        let receipt = pay_as_admin(&env, &client, &contract_id, &admin, 1, 500, &payer);
+
+        reverse_as_admin(&env, &client, &contract_id, &admin, 1, receipt.id, 0xCC);
+        reverse_as_admin(&env, &client, &contract_id, &admin, 1, receipt.id, 0xDD);
+    }
+
+    #[test]
+    #[should_panic(expected = "tx_id already recorded")]
+    fn test_reverse_receipt_retry_with_same_tx_id_fails() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let payer = Address::generate(&env);
+        let first = pay_as_admin(&env, &client, &contract_id, &admin, 1, 500, &payer);
+        let second = pay_as_admin(&env, &client, &contract_id, &admin, 1, 500, &payer);
+
+        reverse_as_admin(&env, &client, &contract_id, &admin, 1, first.id, 0xCC);
+        reverse_as_admin(&env, &client, &contract_id, &admin, 1, second.id, 0xCC);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_non_admin_cannot_reverse_receipt() {
+        let env = Env::default();
+        let (_admin, client, contract_id) = setup(&env);
+        let payer = Address::generate(&env);
+
+        let reason = Symbol::new(&env, "chargeback");
+        let tx_id = BytesN::from_array(&env, &[0xCCu8; 32]);
+        env.mock_auths(&[MockAuth {
+            address: &payer,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "reverse_receipt",
+                args: (1u64, 1u64, reason.clone(), tx_id.clone()).into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+        client.reverse_receipt(&1u64, &1u64, &reason, &tx_id);
+    }
+
+    /// Replay a deal's receipts the way an external auditor would
+    fn recompute_chain(env: &Env, receipts: &soroban_sdk::Vec<Receipt>) -> BytesN<32> {
+        let mut head = BytesN::from_array(env, &[0u8; 32]);
+        for receipt in receipts.iter() {
+            let mut recorded = receipt.clone();
+            recorded.reversed_by = None;
+            recorded.chain_hash = None;
+            let mut preimage = soroban_sdk::Bytes::from_array(env, &head.to_array());
+            preimage.append(&recorded.to_xdr(env));
+            head = env.crypto().sha256(&preimage).into();
+            assert_eq!(receipt.chain_hash, Some(head.clone()));
+        }
+        head
+    }
+
+    #[test]
+    fn test_receipt_chain_head_matches_replay() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let payer = Address::generate(&env);
+
+        assert_eq!(client.receipt_chain_head(&1u64), BytesN::from_array(&env, &[0u8; 32]));
+
+        for i in 1..=4u64 {
+            create_receipt_at(&env, &client, &contract_id, &admin, 1, &payer, i * 100);
+        }
+        reverse_as_admin(&env, &client, &contract_id, &admin, 1, 2, 0xCC);
+
+        let receipts = client.list_receipts_by_deal(&1u64, &100u32, &None).receipts;
+        assert_eq!(recompute_chain(&env, &receipts), client.receipt_chain_head(&1u64));
+    }
+
+    #[test]
+    fn test_receipt_chains_are_per_deal() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let payer = Address::generate(&env);
+
+        let first = pay_as_admin(&env, &client, &contract_id, &admin, 1, 500, &payer);
+        let other = pay_as_admin(&env, &client, &contract_id, &admin, 2, 500, &payer);
+
+        assert_eq!(client.receipt_chain_head(&1u64), first.chain_hash.unwrap());
+        assert_eq!(client.receipt_chain_head(&2u64), other.chain_hash.unwrap());
+        assert_ne!(client.receipt_chain_head(&1u64), client.receipt_chain_head(&2u64));
+    }
+
+    #[test]
+    fn test_receipt_chain_detects_tampering() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let payer = Address::generate(&env);
+        for i in 1..=3u64 {
+            create_receipt_at(&env, &client, &contract_id, &admin, 1, &payer, i * 100);
+        }
+        let head = client.receipt_chain_head(&1u64);
+
+        // Swapping two receipts, or altering an amount, no longer replays to the head
+        let receipts = client.list_receipts_by_deal(&1u64, &100u32, &None).receipts;
+        let replay = |receipts: soroban_sdk::Vec<Receipt>| {
+            let mut h = BytesN::from_array(&env, &[0u8; 32]);
+            for mut r in receipts.iter() {
+                r.chain_hash = None;
+                let mut preimage = soroban_sdk::Bytes::from_array(&env, &h.to_array());
+                preimage.append(&r.to_xdr(&env));
+                h = env.crypto().sha256(&preimage).into();
+            }
+            h
+        };
+        assert_eq!(replay(receipts.clone()), head);
+
+        let mut reordered = receipts.clone();
+        reordered.set(0, receipts.get(1).unwrap());
+        reordered.set(1, receipts.get(0).unwrap());
+        assert_ne!(replay(reordered), head);
+
+        let mut altered = receipts.clone();
+        let mut r = altered.get(2).unwrap();
+        r.amount += 1;
+        altered.set(2, r);
+        assert_ne!(replay(altered), head);
+    }
+
+    /// `receipt_leaf` events published by the last invocation
+    fn leaf_events(env: &Env) -> std::vec::Vec<proofs::LeafEvent> {
+        let mut leaves = std::vec::Vec::new();
+        for (_contract, topics, data) in env.events().all().iter() {
+            let name: Symbol = topics.get(0).unwrap().try_into_val(env).unwrap();
+            if name != Symbol::new(env, "receipt_leaf") {
+                continue;
+            }
+            let deal_id: u64 = topics.get(1).unwrap().try_into_val(env).unwrap();
+            let (receipt_id, leaf_index, leaf_hash): (u64, u64, BytesN<32>) =
+                data.try_into_val(env).unwrap();
+            leaves.push(proofs::LeafEvent {
+                deal_id,
+                receipt_id,
+                leaf_index,
+                leaf_hash: leaf_hash.to_array(),
+            });
+        }
+        leaves
+    }
+
+    /// Record receipts across several deals, collecting their leaf events
+    fn receipts_with_leaves(
+        env: &Env,
+        client: &RentPaymentsClient<'_>,
+        contract_id: &Address,
+        admin: &Address,
+        count: u64,
+    ) -> (std::vec::Vec<Receipt>, std::vec::Vec<proofs::LeafEvent>) {
+        let payer = Address::generate(env);
+        let mut receipts = std::vec::Vec::new();
+        let mut events = std::vec::Vec::new();
+        for i in 0..count {
+            receipts.push(pay_as_admin(env, client, contract_id, admin, i % 3, 100, &payer));
+            events.extend(leaf_events(env));
+        }
+        (receipts, events)
+    }
+
+    #[test]
+    fn test_receipts_root_matches_host_accumulator() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let empty = proofs::ReceiptMmr::new();
+        assert_eq!(client.receipts_root().to_array(), empty.root());
+
+        let (_receipts, events) = receipts_with_leaves(&env, &client, &contract_id, &admin, 11);
+        assert_eq!(events.len(), 11);
+
+        let mmr = proofs::ReceiptMmr::from_events(events.into_iter().rev()).unwrap();
+        assert_eq!(mmr.leaf_count(), 11);
+        assert_eq!(client.receipts_root().to_array(), mmr.root());
+    }
+
+    #[test]
+    fn test_verify_receipt_proof_for_every_receipt() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let (receipts, events) = receipts_with_leaves(&env, &client, &contract_id, &admin, 7);
+        let mmr = proofs::ReceiptMmr::from_events(events.clone()).unwrap();
+        let root = client.receipts_root().to_array();
+
+        for (receipt, event) in receipts.iter().zip(events.iter()) {
+            let leaf_index = receipt.leaf_index.unwrap();
+            assert_eq!(leaf_index, event.leaf_index);
+            let proof = mmr.proof(leaf_index).unwrap();
+            assert!(client.verify_receipt_proof(receipt, &proof.to_contract(&env)));
+            assert!(proofs::verify(&event.leaf_hash, leaf_index, &proof, &root));
+        }
+    }
+
+    #[test]
+    fn test_verify_receipt_proof_after_reversal() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let (receipts, mut events) = receipts_with_leaves(&env, &client, &contract_id, &admin, 4);
+
+        // Marking the original as reversed does not change what was recorded
+        let original = receipts[1].clone();
+        reverse_as_admin(&env, &client, &contract_id, &admin, original.deal_id, original.id, 0xCC);
+        events.extend(leaf_events(&env));
+        let mmr = proofs::ReceiptMmr::from_events(events).unwrap();
+
+        let stored = client.get_receipt_by_tx_id(&original.tx_id).unwrap();
+        assert!(stored.reversed_by.is_some());
+        let proof = mmr.proof(stored.leaf_index.unwrap()).unwrap().to_contract(&env);
+        assert!(client.verify_receipt_proof(&stored, &proof));
+    }
+
+    #[test]
+    fn test_verify_receipt_proof_rejects_tampered_receipt() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let (receipts, events) = receipts_with_leaves(&env, &client, &contract_id, &admin, 5);
+        let mmr = proofs::ReceiptMmr::from_events(events).unwrap();
+
+        let mut receipt = receipts[2].clone();
+        let proof = mmr.proof(receipt.leaf_index.unwrap()).unwrap().to_contract(&env);
+        receipt.amount += 1;
+        assert!(!client.verify_receipt_proof(&receipt, &proof));
+
+        // A proof for one leaf does not verify another
+        let other = mmr.proof(receipts[3].leaf_index.unwrap()).unwrap().to_contract(&env);
+        assert!(!client.verify_receipt_proof(&receipts[2], &other));
+    }
+
+    #[test]
+    fn test_verify_receipt_proof_against_earlier_root() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let (receipts, events) = receipts_with_leaves(&env, &client, &contract_id, &admin, 3);
+        let mmr = proofs::ReceiptMmr::from_events(events).unwrap();
+        let proof = mmr.proof(receipts[0].leaf_index.unwrap()).unwrap().to_contract(&env);
+        let root = client.receipts_root();
+
+        // Later receipts move the current root, but the proof's own root is kept
+        let payer = Address::generate(&env);
+        pay_as_admin(&env, &client, &contract_id, &admin, 9, 100, &payer);
+        assert_ne!(client.receipts_root(), root);
+        assert_eq!(client.receipts_root_at(&3u64), Some(root));
+        assert!(client.verify_receipt_proof(&receipts[0], &proof));
+    }
+
+    #[test]
+    fn test_verify_receipt_proof_rejects_unreached_leaf_count() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let (receipts, events) = receipts_with_leaves(&env, &client, &contract_id, &admin, 3);
+        let mmr = proofs::ReceiptMmr::from_events(events).unwrap();
+        let mut proof = mmr.proof(receipts[0].leaf_index.unwrap()).unwrap().to_contract(&env);
+
+        proof.leaf_count = 4;
+        assert_eq!(client.receipts_root_at(&4u64), None);
+        assert!(!client.verify_receipt_proof(&receipts[0], &proof));
+    }
+
+    #[test]
+    fn test_leaf_stream_must_be_contiguous() {
+        let leaf = |leaf_index| proofs::LeafEvent {
+            deal_id: 1,
+            receipt_id: leaf_index + 1,
+            leaf_index,
+            leaf_hash: [leaf_index as u8; 32],
+        };
+        assert_eq!(
+            proofs::ReceiptMmr::from_events([leaf(0), leaf(2)]).unwrap_err(),
+            proofs::LeafStreamError::Missing(1)
+        );
+        assert_eq!(
+            proofs::ReceiptMmr::from_events([leaf(0), leaf(1), leaf(1)]).unwrap_err(),
+            proofs::LeafStreamError::Duplicate(1)
+        );
+    }
+
+    /// A Stellar asset contract with `amount` minted to `holder`
+    fn create_token(env: &Env, holder: &Address, amount: i128) -> Address {
+        let issuer = Address::generate(env);
+        let token = env.register_stellar_asset_contract_v2(issuer).address();
+        env.mock_all_auths();
+        soroban_sdk::token::StellarAssetClient::new(env, &token).mint(holder, &amount);
+        token
+    }
+
+    fn set_deal_payment_as_admin(
+        env: &Env,
+        client: &RentPaymentsClient<'_>,
+        contract_id: &Address,
+        admin: &Address,
+        deal_id: DealId,
+        token: &Address,
+        recipient: &Option<Address>,
+    ) {
+        env.mock_auths(&[MockAuth {
+            address: admin,
+            invoke: &MockAuthInvoke {
+                contract: contract_id,
+                fn_name: "set_deal_payment",
+                args: (deal_id, token.clone(), recipient.clone()).into_val(env),
+                sub_invokes: &[],
+            },
+        }]);
+        client.set_deal_payment(&deal_id, token, recipient);
+    }
+
+    #[test]
+    fn test_pay_transfers_to_deal_recipient() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        let landlord_account = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+        let token = create_token(&env, &tenant, 1000);
+        set_deal_payment_as_admin(
+            &env,
+            &client,
+            &contract_id,
+            &admin,
+            1,
+            &token,
+            &Some(landlord_account.clone()),
+        );
+
+        env.mock_all_auths();
+        let receipt = client.pay(&1u64, &tenant, &300i128);
+
+        // The payer, not the admin, authorized the payment and the transfer
+        let auths = env.auths();
+        assert_eq!(auths.len(), 1);
+        assert_eq!(auths[0].0, tenant);
+
+        let balances = soroban_sdk::token::Client::new(&env, &token);
+        assert_eq!(balances.balance(&tenant), 700);
+        assert_eq!(balances.balance(&landlord_account), 300);
+
+        assert_eq!(receipt.payer, tenant);
+        assert_eq!(receipt.kind, ReceiptKind::TenantRepayment);
+        assert_eq!(receipt.token, Some(token));
+        assert_eq!(receipt.from, Some(tenant.clone()));
+        assert_eq!(receipt.to, Some(landlord_account));
+        assert_eq!(client.deal_summary(&1u64).outstanding, 600);
+    }
+
+    #[test]
+    fn test_pay_falls_back_to_treasury() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        let treasury = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+        let token = create_token(&env, &tenant, 1000);
+        set_deal_payment_as_admin(&env, &client, &contract_id, &admin, 1, &token, &None);
+
+        env.mock_auths(&[MockAuth {
+            address: &admin,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "set_treasury",
+                args: (treasury.clone(),).into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+        client.set_treasury(&treasury);
+        assert_eq!(client.treasury(), Some(treasury.clone()));
+
+        env.mock_all_auths();
+        client.pay(&1u64, &tenant, &900i128);
+
+        let balances = soroban_sdk::token::Client::new(&env, &token);
+        assert_eq!(balances.balance(&treasury), 900);
+        assert_eq!(client.get_deal(&1u64).status, DealStatus::Completed);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_pay_requires_payer_auth() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+        let token = create_token(&env, &tenant, 1000);
+        set_deal_payment_as_admin(
+            &env,
+            &client,
+            &contract_id,
+            &admin,
+            1,
+            &token,
+            &Some(Address::generate(&env)),
+        );
+
+        // Even the admin cannot pay on the tenant's behalf
+        env.mock_auths(&[MockAuth {
+            address: &admin,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "pay",
+                args: (1u64, tenant.clone(), 300i128).into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+        client.pay(&1u64, &tenant, &300i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "deal has no payment token")]
+    fn test_pay_requires_payment_token() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+
+        env.mock_all_auths();
+        client.pay(&1u64, &tenant, &300i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "no payment recipient configured")]
+    fn test_pay_requires_recipient_or_treasury() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+        let token = create_token(&env, &tenant, 1000);
+        set_deal_payment_as_admin(&env, &client, &contract_id, &admin, 1, &token, &None);
+
+        env.mock_all_auths();
+        client.pay(&1u64, &tenant, &300i128);
+    }
+
+    /// Deal 1 (900 over 3 installments, first due at day 30) paying into a
+    /// fresh token, with a 300-per-installment mandate valid for 120 days
+    fn setup_mandate<'a>(
+        env: &Env,
+        client: &RentPaymentsClient<'a>,
+        contract_id: &Address,
+        admin: &Address,
+        tenant: &Address,
+        recipient: &Address,
+    ) -> soroban_sdk::token::Client<'a> {
+        create_deal_as_admin(env, client, contract_id, admin, 1, tenant, 900, 3, 30 * DAY);
+        let token = create_token(env, tenant, 1000);
+        set_deal_payment_as_admin(env, client, contract_id, admin, 1, &token, &Some(recipient.clone()));
+
+        env.mock_all_auths();
+        let token_client = soroban_sdk::token::Client::new(env, &token);
+        token_client.approve(tenant, contract_id, &900, &1000);
+        client.create_mandate(&1u64, &300i128, &(120 * DAY));
+        token_client
+    }
+
+    #[test]
+    fn test_execute_due_collects_installment() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        let recipient = Address::generate(&env);
+        let token = setup_mandate(&env, &client, &contract_id, &admin, &tenant, &recipient);
+
+        // The tenant signed the mandate itself
+        assert_eq!(env.auths()[0].0, tenant);
+        let mandate = client.get_mandate(&1u64).unwrap();
+        assert_eq!(mandate.payer, tenant);
+        assert_eq!(mandate.token, token.address);
+        assert_eq!(mandate.recipient, recipient);
+        assert_eq!(mandate.max_per_installment, 300);
+
+        // The keeper needs no authorization
+        env.set_auths(&[]);
+        env.ledger().set_timestamp(30 * DAY);
+        let receipt = client.execute_due(&1u64);
+
+        assert_eq!(receipt.amount, 300);
+        assert_eq!(receipt.payer, tenant);
+        assert_eq!(receipt.kind, ReceiptKind::TenantRepayment);
+        assert_eq!(receipt.to, Some(recipient.clone()));
+        assert_eq!(token.balance(&tenant), 700);
+        assert_eq!(token.balance(&recipient), 300);
+
+        let schedule = client.get_schedule(&1u64);
+        assert_eq!(schedule.get(0).unwrap().paid_at, Some(30 * DAY));
+        assert_eq!(schedule.get(1).unwrap().paid, 0);
+
+        env.ledger().set_timestamp(60 * DAY);
+        client.execute_due(&1u64);
+        assert_eq!(token.balance(&recipient), 600);
+        assert_eq!(client.deal_summary(&1u64).outstanding, 300);
+    }
+
+    #[test]
+    #[should_panic(expected = "no installment due")]
+    fn test_execute_due_rejects_early_execution() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        setup_mandate(&env, &client, &contract_id, &admin, &tenant, &Address::generate(&env));
+
+        env.ledger().set_timestamp(30 * DAY);
+        client.execute_due(&1u64);
+        // The next installment is not due until day 60
+        client.execute_due(&1u64);
+    }
+
+    #[test]
+    #[should_panic(expected = "installment exceeds mandate limit")]
+    fn test_execute_due_respects_mandate_limit() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        setup_mandate(&env, &client, &contract_id, &admin, &tenant, &Address::generate(&env));
+
+        client.create_mandate(&1u64, &299i128, &(120 * DAY));
+        env.ledger().set_timestamp(30 * DAY);
+        client.execute_due(&1u64);
+    }
+
+    #[test]
+    #[should_panic(expected = "payment route changed since mandate")]
+    fn test_execute_due_rejects_redirected_payments() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        let token = setup_mandate(&env, &client, &contract_id, &admin, &tenant, &Address::generate(&env));
+
+        let redirected = Some(Address::generate(&env));
+        set_deal_payment_as_admin(&env, &client, &contract_id, &admin, 1, &token.address, &redirected);
+        env.ledger().set_timestamp(30 * DAY);
+        client.execute_due(&1u64);
+    }
+
+    #[test]
+    #[should_panic(expected = "mandate expired")]
+    fn test_execute_due_rejects_expired_mandate() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        setup_mandate(&env, &client, &contract_id, &admin, &tenant, &Address::generate(&env));
+
+        env.ledger().set_timestamp(120 * DAY + 1);
+        client.execute_due(&1u64);
+    }
+
+    #[test]
+    #[should_panic(expected = "no mandate for deal")]
+    fn test_revoked_mandate_cannot_execute() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        setup_mandate(&env, &client, &contract_id, &admin, &tenant, &Address::generate(&env));
+
+        client.revoke_mandate(&1u64);
+        assert_eq!(env.auths()[0].0, tenant);
+        assert_eq!(client.get_mandate(&1u64), None);
+
+        env.ledger().set_timestamp(30 * DAY);
+        client.execute_due(&1u64);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_create_mandate_requires_tenant_auth() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+        let token = create_token(&env, &tenant, 1000);
+        set_deal_payment_as_admin(&env, &client, &contract_id, &admin, 1, &token, &None);
+
+        env.mock_auths(&[MockAuth {
+            address: &admin,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "create_mandate",
+                args: (1u64, 300i128, 120 * DAY).into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+        client.create_mandate(&1u64, &300i128, &(120 * DAY));
+    }
+
+    fn assess_fee_as_admin(
+        env: &Env,
+        client: &RentPaymentsClient<'_>,
+        contract_id: &Address,
+        admin: &Address,
+        deal_id: DealId,
+        amount: i128,
+    ) {
+        let reason = Symbol::new(env, "late_fee");
+        env.mock_auths(&[MockAuth {
+            address: admin,
+            invoke: &MockAuthInvoke {
+                contract: contract_id,
+                fn_name: "assess_fee",
+                args: (deal_id, amount, reason.clone()).into_val(env),
+                sub_invokes: &[],
+            },
+        }]);
+        client.assess_fee(&deal_id, &amount, &reason);
+    }
+
+    #[test]
+    fn test_payoff_quote_includes_fees_and_discount() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+        pay_as_admin(&env, &client, &contract_id, &admin, 1, 300, &tenant);
+
+        let quote = client.payoff_quote(&1u64);
+        assert_eq!((quote.outstanding, quote.fees, quote.discount, quote.amount), (600, 0, 0, 600));
+
+        assess_fee_as_admin(&env, &client, &contract_id, &admin, 1, 25);
+        env.mock_auths(&[MockAuth {
+            address: &admin,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "set_settlement_discount",
+                args: (1u64, 500u32).into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+        client.set_settlement_discount(&1u64, &500u32);
+
+        // 5% off the 600 outstanding, fees payable in full
+        let quote = client.payoff_quote(&1u64);
+        assert_eq!((quote.outstanding, quote.fees, quote.discount, quote.amount), (600, 25, 30, 595));
+        assert_eq!(client.deal_summary(&1u64).fees_due, 25);
+    }
+
+    #[test]
+    fn test_settle_early_closes_deal() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        let recipient = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+        let token = create_token(&env, &tenant, 1000);
+        set_deal_payment_as_admin(&env, &client, &contract_id, &admin, 1, &token, &Some(recipient.clone()));
+
+        env.mock_all_auths();
+        client.pay(&1u64, &tenant, &300i128);
+        assess_fee_as_admin(&env, &client, &contract_id, &admin, 1, 25);
+        env.mock_all_auths();
+        client.set_settlement_discount(&1u64, &1000u32);
+
+        env.ledger().set_timestamp(40 * DAY);
+        let receipt = client.settle_early(&1u64, &tenant);
+        assert_eq!(env.auths()[0].0, tenant);
+
+        // 600 outstanding + 25 fees - 60 discount
+        assert_eq!(receipt.amount, 565);
+        assert_eq!(receipt.kind, ReceiptKind::EarlySettlement);
+        let balances = soroban_sdk::token::Client::new(&env, &token);
+        assert_eq!(balances.balance(&tenant), 135);
+        assert_eq!(balances.balance(&recipient), 865);
+
+        assert_eq!(client.get_deal(&1u64).status, DealStatus::Completed);
+        for installment in client.get_schedule(&1u64).iter().skip(1) {
+            assert_eq!(installment.paid, installment.amount);
+            assert_eq!(installment.paid_at, Some(40 * DAY));
+        }
+
+        let summary = client.deal_summary(&1u64);
+        assert_eq!(summary.outstanding, 0);
+        assert_eq!(summary.fees_due, 0);
+        let position = client.deal_position(&1u64);
+        assert_eq!((position.status, position.principal, position.outstanding), (DealStatus::Completed, 900, 0));
+        assert_eq!(summary.total_paid, 865);
+        assert_eq!(summary.receipt_count, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "deal is not active")]
+    fn test_settle_early_rejects_completed_deal() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+        let token = create_token(&env, &tenant, 2000);
+        set_deal_payment_as_admin(&env, &client, &contract_id, &admin, 1, &token, &Some(Address::generate(&env)));
+
+        env.mock_all_auths();
+        client.settle_early(&1u64, &tenant);
+        client.settle_early(&1u64, &tenant);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot reverse an early settlement")]
+    fn test_early_settlement_cannot_be_reversed() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+        let token = create_token(&env, &tenant, 1000);
+        set_deal_payment_as_admin(&env, &client, &contract_id, &admin, 1, &token, &Some(Address::generate(&env)));
+
+        env.mock_all_auths();
+        let receipt = client.settle_early(&1u64, &tenant);
+        reverse_as_admin(&env, &client, &contract_id, &admin, 1, receipt.id, 9);
+    }
+
+    fn restructure_as_admin(
+        env: &Env,
+        client: &RentPaymentsClient<'_>,
+        contract_id: &Address,
+        admin: &Address,
+        deal_id: DealId,
+        new_term: u32,
+        first_due: Timestamp,
+    ) -> Vec<Installment> {
+        env.mock_auths(&[MockAuth {
+            address: admin,
+            invoke: &MockAuthInvoke {
+                contract: contract_id,
+                fn_name: "restructure_deal",
+                args: (deal_id, new_term, first_due).into_val(env),
+                sub_invokes: &[],
+            },
+        }]);
+        client.restructure_deal(&deal_id, &new_term, &first_due)
+    }
+
+    #[test]
+    fn test_restructure_deal_reschedules_unpaid_installments() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 600, 6, 30 * DAY);
+        env.ledger().set_timestamp(30 * DAY);
+        pay_as_admin(&env, &client, &contract_id, &admin, 1, 100, &tenant);
+        let original = client.get_schedule(&1u64);
+
+        // Stretch the remaining 500 over 10 months, skipping one due date
+        let schedule = restructure_as_admin(&env, &client, &contract_id, &admin, 1, 10, 90 * DAY);
+
+        let event = env.events().all().last().unwrap();
+        assert_eq!(
+            event.1,
+            (Symbol::new(&env, "deal_restructured"), 1u64).into_val(&env)
+        );
+        let data: (u32, (u32, i128), (u32, i128)) = event.2.try_into_val(&env).unwrap();
+        assert_eq!(data, (2, (6, 600), (11, 600)));
+
+        assert_eq!(schedule.len(), 11);
+        assert_eq!(schedule.get(0).unwrap(), original.get(0).unwrap());
+        for (i, installment) in schedule.iter().skip(1).enumerate() {
+            assert_eq!(installment.period, i as u32 + 2);
+            assert_eq!(installment.amount, 50);
+            assert_eq!(installment.paid, 0);
+            assert_eq!(installment.due_date, 90 * DAY + i as u64 * INSTALLMENT_INTERVAL);
+        }
+        assert_eq!(client.get_schedule(&1u64), schedule);
+        assert_eq!(client.deal_summary(&1u64).outstanding, 500);
+
+        let deal = client.get_deal(&1u64);
+        assert_eq!(deal.term, 11);
+        assert_eq!(deal.schedule_version, 2);
+        assert_eq!(client.get_prior_schedule(&1u64, &1u32), original);
+    }
+
+    #[test]
+    fn test_restructure_deal_closes_partial_installment() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 600, 3, 30 * DAY);
+        pay_as_admin(&env, &client, &contract_id, &admin, 1, 250, &tenant);
+
+        env.ledger().set_timestamp(45 * DAY);
+        let schedule = restructure_as_admin(&env, &client, &contract_id, &admin, 1, 2, 60 * DAY);
+
+        // 200 paid in full, 50 of the second installment kept, 350 rescheduled
+        assert_eq!(schedule.len(), 4);
+        assert_eq!(schedule.get(1).unwrap().amount, 50);
+        assert_eq!(schedule.get(1).unwrap().paid_at, Some(45 * DAY));
+        let record = client.tenant_record(&tenant);
+        assert_eq!((record.installments_on_time, record.installments_late), (2, 0));
+        assert_eq!(schedule.get(2).unwrap().amount, 175);
+        assert_eq!(schedule.get(3).unwrap().amount, 175);
+
+        // Repayments apply to the new installments and can still complete the deal
+        pay_as_admin(&env, &client, &contract_id, &admin, 1, 350, &tenant);
+        assert_eq!(client.get_deal(&1u64).status, DealStatus::Completed);
+    }
+
+    #[test]
+    #[should_panic(expected = "first_due must not be in the past")]
+    fn test_restructure_deal_rejects_past_first_due() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 600, 3, 30 * DAY);
+
+        env.ledger().set_timestamp(45 * DAY);
+        restructure_as_admin(&env, &client, &contract_id, &admin, 1, 2, 40 * DAY);
+    }
+
+    #[test]
+    #[should_panic(expected = "first_due must not precede a paid installment")]
+    fn test_restructure_deal_rejects_first_due_before_paid_installments() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 600, 3, 30 * DAY);
+        // Prepaying the first two installments, due on days 30 and 60
+        pay_as_admin(&env, &client, &contract_id, &admin, 1, 400, &tenant);
+
+        restructure_as_admin(&env, &client, &contract_id, &admin, 1, 2, 45 * DAY);
+    }
+
+    #[test]
+    #[should_panic(expected = "deal is not active")]
+    fn test_restructure_deal_rejects_completed_deal() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 600, 3, 30 * DAY);
+        pay_as_admin(&env, &client, &contract_id, &admin, 1, 600, &tenant);
+
+        restructure_as_admin(&env, &client, &contract_id, &admin, 1, 6, 90 * DAY);
+    }
+
+    fn allocation(fee: i128, arrears: i128, current: i128, prepayment: i128) -> Allocation {
+        Allocation { fee, arrears, current, prepayment }
+    }
+
+    #[test]
+    fn test_repayment_allocates_fees_first_by_default() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+        assert_eq!(client.get_deal(&1u64).allocation_policy, AllocationPolicy::FeesFirst);
+        assess_fee_as_admin(&env, &client, &contract_id, &admin, 1, 20);
+
+        // Day 40: the first installment is past its grace period, the second is current
+        env.ledger().set_timestamp(40 * DAY);
+        let receipt = pay_as_admin(&env, &client, &contract_id, &admin, 1, 450, &tenant);
+        assert_eq!(receipt.allocations, allocation(20, 300, 130, 0));
+
+        let receipt = pay_as_admin(&env, &client, &contract_id, &admin, 1, 300, &tenant);
+        assert_eq!(receipt.allocations, allocation(0, 0, 170, 130));
+        assert_eq!(client.deal_summary(&1u64).fees_due, 0);
+        assert_eq!(client.deal_summary(&1u64).outstanding, 170);
+    }
+
+    #[test]
+    fn test_repayment_allocates_rent_before_fees() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+        assess_fee_as_admin(&env, &client, &contract_id, &admin, 1, 20);
+        env.mock_auths(&[MockAuth {
+            address: &admin,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "set_allocation_policy",
+                args: (1u64, AllocationPolicy::RentFirst).into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+        client.set_allocation_policy(&1u64, &AllocationPolicy::RentFirst);
+
+        env.ledger().set_timestamp(40 * DAY);
+        let receipt = pay_as_admin(&env, &client, &contract_id, &admin, 1, 650, &tenant);
+        assert_eq!(receipt.allocations, allocation(20, 300, 300, 30));
+
+        // Paying off the schedule completes the deal only once fees are clear too
+        let receipt = pay_as_admin(&env, &client, &contract_id, &admin, 1, 270, &tenant);
+        assert_eq!(receipt.allocations, allocation(0, 0, 270, 0));
+        assert_eq!(client.get_deal(&1u64).status, DealStatus::Completed);
+    }
+
+    #[test]
+    fn test_reversal_restores_allocated_fees() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+        assess_fee_as_admin(&env, &client, &contract_id, &admin, 1, 20);
+        env.ledger().set_timestamp(40 * DAY);
+        let receipt = pay_as_admin(&env, &client, &contract_id, &admin, 1, 320, &tenant);
+        assert_eq!(receipt.allocations, allocation(20, 300, 0, 0));
+
+        let reversal = reverse_as_admin(&env, &client, &contract_id, &admin, 1, receipt.id, 9);
+        assert_eq!(reversal.allocations, allocation(-20, -300, 0, 0));
+
+        let summary = client.deal_summary(&1u64);
+        assert_eq!(summary.fees_due, 20);
+        assert_eq!(summary.outstanding, 900);
+    }
+
+    #[test]
+    fn test_early_settlement_records_allocations() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+        let token = create_token(&env, &tenant, 1000);
+        set_deal_payment_as_admin(&env, &client, &contract_id, &admin, 1, &token, &Some(Address::generate(&env)));
+        assess_fee_as_admin(&env, &client, &contract_id, &admin, 1, 20);
+
+        env.mock_all_auths();
+        client.set_settlement_discount(&1u64, &1000u32);
+        env.ledger().set_timestamp(40 * DAY);
+        let receipt = client.settle_early(&1u64, &tenant);
+
+        // 900 + 20 - 90: the discount comes off the last installment
+        assert_eq!(receipt.amount, 830);
+        assert_eq!(receipt.allocations, allocation(20, 300, 300, 210));
+        assert_eq!(client.get_deal(&1u64).status, DealStatus::Completed);
+    }
+
+    #[test]
+    fn test_statement_reports_monthly_activity() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        env.ledger().set_timestamp(DAY);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+
+        env.ledger().set_timestamp(30 * DAY);
+        pay_as_admin(&env, &client, &contract_id, &admin, 1, 300, &tenant);
+        env.ledger().set_timestamp(66 * DAY);
+        assess_fee_as_admin(&env, &client, &contract_id, &admin, 1, 20);
+        let receipt = pay_as_admin(&env, &client, &contract_id, &admin, 1, 320, &tenant);
+        env.ledger().set_timestamp(70 * DAY);
+        let reversal = reverse_as_admin(&env, &client, &contract_id, &admin, 1, receipt.id, 9);
+
+        // First month: the deal is opened and the first installment paid
+        let first = client.statement(&1u64, &0, &(31 * DAY - 1));
+        assert_eq!(first.opening_balance, 0);
+        assert_eq!(first.advanced, 900);
+        assert_eq!(first.payments, 300);
+        assert_eq!(first.receipts.len(), 1);
+        assert_eq!(first.closing_balance, 600);
+
+        // Third month: a late fee, a payment and its reversal
+        let third = client.statement(&1u64, &(61 * DAY), &(91 * DAY - 1));
+        assert_eq!(third.opening_balance, 600);
+        assert_eq!(third.advanced, 0);
+        assert_eq!(third.fees_accrued, 20);
+        assert_eq!(third.fees.get(0).unwrap().timestamp, 66 * DAY);
+        assert_eq!(third.payments, 0);
+        assert_eq!(third.allocations, Allocation::none());
+        assert_eq!(third.receipts, vec![&env, client.get_receipt_by_tx_id(&receipt.tx_id).unwrap(), reversal]);
+        assert_eq!(third.closing_balance, 620);
+
+        // An empty month carries the balance over
+        let second = client.statement(&1u64, &(31 * DAY), &(61 * DAY - 1));
+        assert_eq!(second.opening_balance, 600);
+        assert_eq!(second.receipts.len(), 0);
+        assert_eq!(second.closing_balance, 600);
+
+        let summary = client.deal_summary(&1u64);
+        assert_eq!(third.closing_balance, summary.outstanding + summary.fees_due);
+    }
+
+    #[test]
+    fn test_statement_accounts_for_settlement_discount() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+        let token = create_token(&env, &tenant, 1000);
+        set_deal_payment_as_admin(&env, &client, &contract_id, &admin, 1, &token, &Some(Address::generate(&env)));
+
+        env.mock_all_auths();
+        client.set_settlement_discount(&1u64, &1000u32);
+        env.ledger().set_timestamp(10 * DAY);
+        client.settle_early(&1u64, &tenant);
+
+        let statement = client.statement(&1u64, &0, &(30 * DAY));
+        assert_eq!(statement.advanced, 900);
+        assert_eq!(statement.payments, 810);
+        assert_eq!(statement.discounts, 90);
+        assert_eq!(statement.allocations.prepayment, 510);
+        assert_eq!(statement.closing_balance, 0);
+    }
+
+    #[test]
+    fn test_statement_ignores_unallocated_payments() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+        pay_as_admin(&env, &client, &contract_id, &admin, 1, 900, &tenant);
+        // The deal is completed, so this payment is recorded without an allocation
+        let excess = pay_as_admin(&env, &client, &contract_id, &admin, 1, 50, &tenant);
+
+        let statement = client.statement(&1u64, &0, &(30 * DAY));
+        assert_eq!(statement.payments, 900);
+        assert_eq!(statement.receipts.len(), 2);
+        assert_eq!(statement.closing_balance, 0);
+
+        reverse_as_admin(&env, &client, &contract_id, &admin, 1, excess.id, 0xCC);
+        let statement = client.statement(&1u64, &0, &(30 * DAY));
+        assert_eq!(statement.payments, 900);
+        assert_eq!(statement.closing_balance, 0);
+    }
+
+    #[test]
+    fn test_tenant_statement_covers_all_deals() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 2, &Address::generate(&env), 500, 1, 30 * DAY);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 3, &tenant, 600, 2, 30 * DAY);
+        pay_as_admin(&env, &client, &contract_id, &admin, 3, 300, &tenant);
+
+        let statements = client.tenant_statement(&tenant, &0, &(30 * DAY));
+        assert_eq!(statements.len(), 2);
+        assert_eq!(statements.get(0).unwrap().deal_id, 1);
+        assert_eq!(statements.get(0).unwrap().closing_balance, 900);
+        assert_eq!(statements.get(1).unwrap().deal_id, 3);
+        assert_eq!(statements.get(1).unwrap().closing_balance, 300);
+    }
+
+    #[test]
+    #[should_panic(expected = "period_start must not be after period_end")]
+    fn test_statement_rejects_inverted_period() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+
+        client.statement(&1u64, &(30 * DAY), &DAY);
+    }
+
+    #[test]
+    fn test_tenant_record_tracks_history_across_deals() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 600, 2, 30 * DAY);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 2, &tenant, 900, 3, 30 * DAY);
+
+        // Deal 1: one installment on time, one after its grace period
+        env.ledger().set_timestamp(30 * DAY);
+        pay_as_admin(&env, &client, &contract_id, &admin, 1, 300, &tenant);
+        env.ledger().set_timestamp(70 * DAY);
+        pay_as_admin(&env, &client, &contract_id, &admin, 1, 300, &tenant);
+
+        // Deal 2: one installment paid, then the tenant stops paying
+        pay_as_admin(&env, &client, &contract_id, &admin, 2, 300, &tenant);
+        env.mock_auths(&[MockAuth {
+            address: &admin,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "mark_default",
+                args: (2u64,).into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+        client.mark_default(&2u64);
+        assert_eq!(client.deal_status(&2u64), DealStatus::Defaulted);
+
+        let record = client.tenant_record(&tenant);
+        assert_eq!(record.installments_on_time, 1);
+        assert_eq!(record.installments_late, 2);
+        assert_eq!(record.installments_defaulted, 2);
+        assert_eq!(record.deals_completed, 1);
+        assert_eq!(record.deals_defaulted, 1);
+        assert_eq!(record.total_repaid, 900);
+
+        let other = client.tenant_record(&Address::generate(&env));
+        assert_eq!(other.total_repaid, 0);
+        assert_eq!(other.deals_completed, 0);
+    }
+
+    #[test]
+    fn test_reversal_takes_back_tenant_record() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 600, 2, 30 * DAY);
+        let receipt = pay_as_admin(&env, &client, &contract_id, &admin, 1, 600, &tenant);
+        assert_eq!(client.tenant_record(&tenant).installments_on_time, 2);
+        assert_eq!(client.tenant_record(&tenant).deals_completed, 1);
+
+        reverse_as_admin(&env, &client, &contract_id, &admin, 1, receipt.id, 9);
+
+        let record = client.tenant_record(&tenant);
+        assert_eq!(record.installments_on_time, 0);
+        assert_eq!(record.deals_completed, 0);
+        assert_eq!(record.total_repaid, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "deal is not active")]
+    fn test_defaulted_deal_rejects_repayments() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 600, 2, 30 * DAY);
+        env.mock_all_auths();
+        client.mark_default(&1u64);
+
+        let kind = ReceiptKind::TenantRepayment;
+        client.record_receipt(&1u64, &kind, &300i128, &tenant, &ReceiptDetails::none());
+    }
+
+    /// A listings contract wired to `contract_id`, holding one submitted
+    /// (and optionally approved) listing
+    fn setup_listing(env: &Env, contract_id: &Address, approved: bool) -> (Address, u64) {
+        let listings_id = env.register(listings::Listings, ());
+        let listings_client = listings::ListingsClient::new(env, &listings_id);
+        env.mock_all_auths();
+        listings_client.init(&Address::generate(env));
+        listings_client.set_deals_contract(contract_id);
+        let listing_id = listings_client.submit_listing(&Address::generate(env), &BytesN::from_array(env, &[5; 32]));
+        if approved {
+            listings_client.approve_listing(&listing_id);
+        }
+        (listings_id, listing_id)
+    }
+
+    fn link_listing_as_admin(
+        env: &Env,
+        client: &RentPaymentsClient<'_>,
+        contract_id: &Address,
+        admin: &Address,
+        listings_id: &Address,
+        deal_id: DealId,
+        listing_id: u64,
+    ) {
+        env.mock_auths(&[
+            MockAuth {
+                address: admin,
+                invoke: &MockAuthInvoke {
+                    contract: contract_id,
+                    fn_name: "set_listings_contract",
+                    args: (listings_id.clone(),).into_val(env),
+                    sub_invokes: &[],
+                },
+            },
+            MockAuth {
+                address: admin,
+                invoke: &MockAuthInvoke {
+                    contract: contract_id,
+                    fn_name: "link_listing",
+                    args: (deal_id, listing_id).into_val(env),
+                    sub_invokes: &[],
+                },
+            },
+        ]);
+        client.set_listings_contract(listings_id);
+        client.link_listing(&deal_id, &listing_id);
+    }
+
+    #[test]
+    fn test_link_listing_marks_listing_rented() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+        let (listings_id, listing_id) = setup_listing(&env, &contract_id, true);
 
-    use super::*;
-    use soroban_sdk::{
-        testutils::{Address as _, MockAuth, MockAuthInvoke},
-        Address, BytesN, Env, IntoVal,
-    };
+        link_listing_as_admin(&env, &client, &contract_id, &admin, &listings_id, 1, listing_id);
 
-    fn setup(env: &Env) -> (Address, RentPaymentsClient<'_>, soroban_sdk::Address) {
-        let contract_id = env.register_contract(None, RentPayments);
-        // Note: register_contract is deprecated but still works in SDK 22.0.7
-        let client = RentPaymentsClient::new(env, &contract_id);
-        let admin = Address::generate(env);
-        client.init(&admin);
-        (admin, client, contract_id)
+        assert_eq!(client.get_deal(&1u64).listing_id, Some(listing_id));
+        assert_eq!(client.deal_listing(&1u64), Some(listing_id));
+        let listing = listings::ListingsClient::new(&env, &listings_id).get_listing(&listing_id);
+        assert_eq!(listing.status, listings::ListingStatus::Rented);
+        assert_eq!(listing.deal_id, Some(1));
     }
 
+    #[test]
+    #[should_panic]
+    fn test_link_listing_requires_approved_listing() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+        let (listings_id, listing_id) = setup_listing(&env, &contract_id, false);
+
+        link_listing_as_admin(&env, &client, &contract_id, &admin, &listings_id, 1, listing_id);
+    }
 
     #[test]
-    fn test_list_receipts_by_deal_empty() {
+    fn test_pay_sends_reserve_cut() {
         let env = Env::default();
-        let (_admin, client, _contract_id) = setup(&env);
-        let deal_id = 1u64;
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        let recipient = Address::generate(&env);
+        let reserve = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+        let token = create_token(&env, &tenant, 1000);
+        set_deal_payment_as_admin(&env, &client, &contract_id, &admin, 1, &token, &Some(recipient.clone()));
 
-        let page = client.list_receipts_by_deal(&deal_id, &10u32, &None);
-        assert_eq!(page.receipts.len(), 0);
-        assert!(!page.has_next);
+        env.mock_all_auths();
+        client.set_reserve(&reserve, &250u32);
+        let receipt = client.pay(&1u64, &tenant, &400i128);
+
+        let event = env.events().all().last().unwrap();
+        assert_eq!(event.1, (Symbol::new(&env, "reserve_cut"), 1u64).into_val(&env));
+        let data: (u64, Address, i128) = event.2.try_into_val(&env).unwrap();
+        assert_eq!(data, (receipt.id, reserve.clone(), 10));
+
+        // The tenant is credited with the full payment
+        let balances = soroban_sdk::token::Client::new(&env, &token);
+        assert_eq!(balances.balance(&reserve), 10);
+        assert_eq!(balances.balance(&recipient), 390);
+        assert_eq!(receipt.amount, 400);
+        assert_eq!(client.deal_summary(&1u64).outstanding, 500);
     }
 
     #[test]
-    fn test_list_receipts_by_deal_single_page() {
+    fn test_total_outstanding_tracks_active_deals() {
         let env = Env::default();
         let (admin, client, contract_id) = setup(&env);
-        let deal_id = 1u64;
-        let payer = Address::generate(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 2, &tenant, 600, 2, 30 * DAY);
+        assert_eq!(client.total_outstanding(), 1500);
 
-        // Create 5 receipts
-        for i in 1..=5 {
-            env.mock_auths(&[MockAuth {
-                address: &admin,
-                invoke: &MockAuthInvoke {
-                    contract: &contract_id,
-                    fn_name: "create_receipt",
-                    args: (deal_id, (i * 1000) as i128, payer.clone()).into_val(&env),
-                    sub_invokes: &[],
-                },
-            }]);
-            client.create_receipt(&deal_id, &(i * 1000), &payer);
-        }
+        let receipt = pay_as_admin(&env, &client, &contract_id, &admin, 1, 300, &tenant);
+        assert_eq!(client.total_outstanding(), 1200);
+        reverse_as_admin(&env, &client, &contract_id, &admin, 1, receipt.id, 9);
+        assert_eq!(client.total_outstanding(), 1500);
 
-        let page = client.list_receipts_by_deal(&deal_id, &10u32, &None);
-        assert_eq!(page.receipts.len(), 5);
-        assert!(!page.has_next);
+        restructure_as_admin(&env, &client, &contract_id, &admin, 1, 6, 60 * DAY);
+        assert_eq!(client.total_outstanding(), 1500);
+
+        env.mock_all_auths();
+        client.mark_default(&2u64);
+        assert_eq!(client.total_outstanding(), 900);
     }
 
     #[test]
-    fn test_list_receipts_by_deal_pagination() {
+    fn test_pay_withholds_protocol_fee() {
         let env = Env::default();
         let (admin, client, contract_id) = setup(&env);
-        let deal_id = 1u64;
-        let payer = Address::generate(&env);
+        let tenant = Address::generate(&env);
+        let recipient = Address::generate(&env);
+        let treasury = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+        let token = create_token(&env, &tenant, 1000);
+        set_deal_payment_as_admin(&env, &client, &contract_id, &admin, 1, &token, &Some(recipient.clone()));
 
-        // Create 15 receipts
-        for i in 1..=15 {
-            env.mock_auths(&[MockAuth {
-                address: &admin,
-                invoke: &MockAuthInvoke {
-                    contract: &contract_id,
-                    fn_name: "create_receipt",
-                    args: (deal_id, (i * 1000) as i128, payer.clone()).into_val(&env),
-                    sub_invokes: &[],
-                },
-            }]);
-            client.create_receipt(&deal_id, &(i * 1000), &payer);
-        }
+        env.mock_all_auths();
+        client.set_treasury(&treasury);
+        client.set_protocol_fee(&ReceiptKind::TenantRepayment, &Some(FeeRate::Bps(100)));
+        let receipt = client.pay(&1u64, &tenant, &400i128);
 
-        // First page: 10 receipts
-        let page1 = client.list_receipts_by_deal(&deal_id, &10u32, &None);
-        assert_eq!(page1.receipts.len(), 10);
-        assert!(page1.has_next);
+        let event = env.events().all().last().unwrap();
+        assert_eq!(event.1, (Symbol::new(&env, "protocol_fee"), 1u64).into_val(&env));
+        let data: (u64, Address, i128) = event.2.try_into_val(&env).unwrap();
+        assert_eq!(data, (receipt.id, treasury.clone(), 4));
 
-        // Second page: remaining 5 receipts
-        let cursor1 = page1.next_cursor.clone();
-        let page2 = client.list_receipts_by_deal(&deal_id, &10u32, &Some(cursor1));
-        assert_eq!(page2.receipts.len(), 5);
-        assert!(!page2.has_next);
+        assert_eq!(receipt.protocol_fee, 4);
+        assert_eq!(client.deal_summary(&1u64).outstanding, 500);
+        let balances = soroban_sdk::token::Client::new(&env, &token);
+        assert_eq!(balances.balance(&treasury), 4);
+        assert_eq!(balances.balance(&recipient), 396);
 
-        // Verify no duplicates between pages
-        let page1_ids: std::vec::Vec<u64> = page1
-            .receipts
-            .iter()
-            .map(|r| r.id)
-            .collect();
-        let page2_ids: std::vec::Vec<u64> = page2
-            .receipts
-            .iter()
-            .map(|r| r.id)
-            .collect();
+        client.set_protocol_fee(&ReceiptKind::TenantRepayment, &Some(FeeRate::Flat(10)));
+        client.pay(&1u64, &tenant, &100i128);
+        assert_eq!(client.fees_collected().get(token.clone()), Some(14));
 
-        for id in &page1_ids {
-            assert!(!page2_ids.contains(id), "Duplicate receipt found: {}", id);
-        }
+        client.set_protocol_fee(&ReceiptKind::TenantRepayment, &None);
+        assert_eq!(client.pay(&1u64, &tenant, &100i128).protocol_fee, 0);
+        assert_eq!(client.fees_collected().get(token), Some(14));
     }
 
     #[test]
-    fn test_list_receipts_by_deal_no_skipping() {
+    fn test_reverse_receipt_returns_protocol_fee() {
         let env = Env::default();
         let (admin, client, contract_id) = setup(&env);
-        let deal_id = 1u64;
-        let payer = Address::generate(&env);
+        let tenant = Address::generate(&env);
+        let treasury = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+        let token = create_token(&env, &tenant, 1000);
+        set_deal_payment_as_admin(&env, &client, &contract_id, &admin, 1, &token, &None);
 
-        // Create 25 receipts
-        for i in 1..=25 {
-            env.mock_auths(&[MockAuth {
-                address: &admin,
-                invoke: &MockAuthInvoke {
-                    contract: &contract_id,
-                    fn_name: "create_receipt",
-                    args: (deal_id, (i * 1000) as i128, payer.clone()).into_val(&env),
-                    sub_invokes: &[],
-                },
-            }]);
-            client.create_receipt(&deal_id, &(i * 1000), &payer);
-        }
+        env.mock_all_auths();
+        client.set_treasury(&treasury);
+        client.set_protocol_fee(&ReceiptKind::TenantRepayment, &Some(FeeRate::Flat(10)));
+        let first = client.pay(&1u64, &tenant, &300i128);
+        client.pay(&1u64, &tenant, &300i128);
+        assert_eq!(client.fees_collected().get(token.clone()), Some(20));
 
-        // Collect all receipts across pages
-        let mut all_receipt_ids = std::vec::Vec::new();
-        let mut cursor = None;
+        let reason = Symbol::new(&env, "refund");
+        let tx_id = BytesN::from_array(&env, &[0xCCu8; 32]);
+        let reversal = client.reverse_receipt(&1u64, &first.id, &reason, &tx_id);
+        assert_eq!(reversal.protocol_fee, -10);
+        assert_eq!(client.fees_collected().get(token), Some(10));
+    }
 
-        loop {
-            let page = client.list_receipts_by_deal(&deal_id, &10u32, &cursor);
+    #[test]
+    #[should_panic(expected = "no treasury configured")]
+    fn test_protocol_fee_requires_treasury() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+        let token = create_token(&env, &tenant, 1000);
+        let recipient = Some(Address::generate(&env));
+        set_deal_payment_as_admin(&env, &client, &contract_id, &admin, 1, &token, &recipient);
 
-            for receipt in page.receipts.iter() {
-                all_receipt_ids.push(receipt.id);
-            }
+        env.mock_all_auths();
+        client.set_protocol_fee(&ReceiptKind::TenantRepayment, &Some(FeeRate::Flat(10)));
+        client.pay(&1u64, &tenant, &100i128);
+    }
 
-            if !page.has_next {
-                break;
-            }
-            cursor = Some(page.next_cursor.clone());
-        }
+    #[test]
+    #[should_panic(expected = "fees exceed payment amount")]
+    fn test_protocol_fee_cannot_exceed_payment() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 900, 3, 30 * DAY);
+        let token = create_token(&env, &tenant, 1000);
+        let recipient = Some(Address::generate(&env));
+        set_deal_payment_as_admin(&env, &client, &contract_id, &admin, 1, &token, &recipient);
 
-        // Verify we got exactly 25 receipts (no skipping)
-        assert_eq!(all_receipt_ids.len(), 25);
-        
-        // Verify all IDs are unique (no duplicates)
-        let mut sorted_ids = all_receipt_ids.clone();
-        sorted_ids.sort();
-        sorted_ids.dedup();
-        assert_eq!(sorted_ids.len(), 25, "Found duplicate receipts");
+        env.mock_all_auths();
+        client.set_treasury(&Address::generate(&env));
+        client.set_protocol_fee(&ReceiptKind::TenantRepayment, &Some(FeeRate::Flat(10)));
+        client.pay(&1u64, &tenant, &5i128);
+    }
+
+    fn setup_fx_oracle(env: &Env, rates: &[i128]) -> Address {
+        let oracle_id = env.register(fx_oracle::FxOracle, ());
+        let oracle = fx_oracle::FxOracleClient::new(env, &oracle_id);
+        let pair = Symbol::new(env, "NGN_USDC");
+        env.mock_all_auths();
+        oracle.init(&Address::generate(env));
+        for rate in rates {
+            let reporter = Address::generate(env);
+            oracle.add_reporter(&reporter);
+            oracle.submit_rate(&reporter, &pair, rate, &env.ledger().timestamp());
+        }
+        oracle_id
     }
 
     #[test]
-    fn test_list_receipts_by_deal_stable_ordering() {
+    fn test_ngn_deal_converts_payments_at_oracle_rate() {
         let env = Env::default();
         let (admin, client, contract_id) = setup(&env);
-        let deal_id = 1u64;
-        let payer = Address::generate(&env);
+        let tenant = Address::generate(&env);
+        let recipient = Address::generate(&env);
+        // ₦300,000 over 3 installments, in kobo
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 30_000_000, 3, 30 * DAY);
+        let token = create_token(&env, &tenant, 10_000_000_000);
+        set_deal_payment_as_admin(&env, &client, &contract_id, &admin, 1, &token, &Some(recipient.clone()));
 
-        // Create multiple receipts
-        for i in 1..=10 {
-            env.mock_auths(&[MockAuth {
-                address: &admin,
-                invoke: &MockAuthInvoke {
-                    contract: &contract_id,
-                    fn_name: "create_receipt",
-                    args: (deal_id, (i * 1000) as i128, payer.clone()).into_val(&env),
-                    sub_invokes: &[],
-                },
-            }]);
-            client.create_receipt(&deal_id, &(i * 1000), &payer);
-        }
+        env.ledger().set_timestamp(30 * DAY);
+        let rates = [1_490 * FX_RATE_SCALE, 1_500 * FX_RATE_SCALE, 1_600 * FX_RATE_SCALE];
+        let oracle = setup_fx_oracle(&env, &rates);
+        client.set_deal_fx(&1u64, &oracle, &Symbol::new(&env, "NGN_USDC"), &DAY, &2u32);
 
-        // Get all receipts in one call
-        let all_page = client.list_receipts_by_deal(&deal_id, &100u32, &None);
-        
-        // Get receipts in pages and verify ordering is consistent
-        let mut cursor = None;
-        let mut prev_timestamp = 0u64;
-        let mut prev_tx_id: Option<BytesN<32>> = None;
+        // ₦100,000 at ₦1,500 per USDC is 66.6666667 USDC, rounded up
+        assert_eq!(client.quote_payment(&1u64, &10_000_000), 666_666_667);
+        let receipt = client.pay(&1u64, &tenant, &10_000_000);
 
-        loop {
-            let page = client.list_receipts_by_deal(&deal_id, &3u32, &cursor);
-            
-            for receipt in page.receipts.iter() {
-                // Verify ordering: timestamp should be >= previous
-                assert!(
-                    receipt.timestamp >= prev_timestamp,
-                    "Receipts not in ascending timestamp order"
-                );
-                
-                // If timestamp is equal, tx_id should be >= previous
-                if receipt.timestamp == prev_timestamp {
-                    if let Some(ref prev) = prev_tx_id {
-                        let receipt_array = receipt.tx_id.to_array();
-                        let prev_array = prev.to_array();
-                        assert!(
-                            receipt_array >= prev_array,
-                            "Receipts with same timestamp not in ascending tx_id order"
-                        );
-                    }
-                }
-                
-                prev_timestamp = receipt.timestamp;
-                prev_tx_id = Some(receipt.tx_id.clone());
-            }
+        assert_eq!(receipt.amount, 666_666_667);
+        assert_eq!(receipt.amount_ngn, Some(10_000_000));
+        assert_eq!(receipt.fx_rate, Some(1_500 * FX_RATE_SCALE));
+        assert_eq!(receipt.fx_provider, Some(Symbol::new(&env, "fx_oracle")));
+        let balances = soroban_sdk::token::Client::new(&env, &token);
+        assert_eq!(balances.balance(&recipient), 666_666_667);
+        assert_eq!(client.deal_summary(&1u64).outstanding, 20_000_000);
+    }
 
-            if !page.has_next {
-                break;
-            }
-            cursor = Some(page.next_cursor.clone());
-        }
+    #[test]
+    #[should_panic]
+    fn test_ngn_deal_rejects_stale_rates() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 30_000_000, 3, 30 * DAY);
+        let token = create_token(&env, &tenant, 10_000_000_000);
+        let recipient = Some(Address::generate(&env));
+        set_deal_payment_as_admin(&env, &client, &contract_id, &admin, 1, &token, &recipient);
 
-        // Verify the full list matches the single-page result
-        let mut paginated_ids: std::vec::Vec<u64> = std::vec::Vec::new();
-        cursor = None;
-        loop {
-            let page = client.list_receipts_by_deal(&deal_id, &3u32, &cursor);
-            for receipt in page.receipts.iter() {
-                paginated_ids.push(receipt.id);
-            }
-            if !page.has_next {
-                break;
-            }
-            cursor = Some(page.next_cursor.clone());
-        }
+        let oracle = setup_fx_oracle(&env, &[1_500 * FX_RATE_SCALE]);
+        client.set_deal_fx(&1u64, &oracle, &Symbol::new(&env, "NGN_USDC"), &DAY, &1u32);
+        env.ledger().set_timestamp(2 * DAY);
 
-        let all_ids: std::vec::Vec<u64> = all_page.receipts.iter().map(|r| r.id).collect();
-        assert_eq!(paginated_ids, all_ids, "Paginated results don't match full results");
+        client.pay(&1u64, &tenant, &10_000_000);
     }
 
     #[test]
-    #[should_panic(expected = "limit must be between 1 and 100")]
-    fn test_list_receipts_by_deal_invalid_limit_zero() {
+    #[should_panic(expected = "deal already has receipts")]
+    fn test_set_deal_fx_rejects_deals_with_receipts() {
         let env = Env::default();
-        let (_admin, client, _contract_id) = setup(&env);
-        let deal_id = 1u64;
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 30_000_000, 3, 30 * DAY);
+        let token = create_token(&env, &tenant, 10_000_000_000);
+        let recipient = Some(Address::generate(&env));
+        set_deal_payment_as_admin(&env, &client, &contract_id, &admin, 1, &token, &recipient);
 
-        client.list_receipts_by_deal(&deal_id, &0u32, &None);
+        env.mock_all_auths();
+        client.pay(&1u64, &tenant, &1_000);
+        let oracle = setup_fx_oracle(&env, &[1_500 * FX_RATE_SCALE]);
+        client.set_deal_fx(&1u64, &oracle, &Symbol::new(&env, "NGN_USDC"), &DAY, &1u32);
     }
 
     #[test]
-    #[should_panic(expected = "limit must be between 1 and 100")]
-    fn test_list_receipts_by_deal_invalid_limit_too_large() {
+    #[should_panic(expected = "installment exceeds mandate limit")]
+    fn test_ngn_mandate_limit_is_in_tokens() {
         let env = Env::default();
-        let (_admin, client, _contract_id) = setup(&env);
-        let deal_id = 1u64;
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 30_000_000, 3, 30 * DAY);
+        let token = create_token(&env, &tenant, 10_000_000_000);
+        let recipient = Some(Address::generate(&env));
+        set_deal_payment_as_admin(&env, &client, &contract_id, &admin, 1, &token, &recipient);
 
-        client.list_receipts_by_deal(&deal_id, &101u32, &None);
+        env.ledger().set_timestamp(30 * DAY);
+        let oracle = setup_fx_oracle(&env, &[1_500 * FX_RATE_SCALE]);
+        client.set_deal_fx(&1u64, &oracle, &Symbol::new(&env, "NGN_USDC"), &DAY, &1u32);
+        soroban_sdk::token::Client::new(&env, &token).approve(&tenant, &contract_id, &10_000_000_000, &1000);
+
+        // ₦100,000 costs 666_666_667 at ₦1,500 per USDC; the limit is in USDC
+        client.create_mandate(&1u64, &666_666_666i128, &(120 * DAY));
+        client.execute_due(&1u64);
     }
 
     #[test]
-    fn test_list_receipts_by_deal_different_deals() {
+    #[should_panic(expected = "amount_ngn required for NGN deals")]
+    fn test_ngn_deal_rejects_receipt_without_amount_ngn() {
         let env = Env::default();
         let (admin, client, contract_id) = setup(&env);
-        let payer = Address::generate(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 30_000_000, 3, 30 * DAY);
+        let oracle = setup_fx_oracle(&env, &[1_500 * FX_RATE_SCALE]);
+        client.set_deal_fx(&1u64, &oracle, &Symbol::new(&env, "NGN_USDC"), &DAY, &1u32);
 
-        // Create receipts for deal 1
-        for i in 1..=5 {
-            env.mock_auths(&[MockAuth {
-                address: &admin,
-                invoke: &MockAuthInvoke {
-                    contract: &contract_id,
-                    fn_name: "create_receipt",
-                    args: (1u64, (i * 1000) as i128, payer.clone()).into_val(&env),
-                    sub_invokes: &[],
-                },
-            }]);
-            client.create_receipt(&1u64, &(i * 1000), &payer);
-        }
+        // 1_000_000_000 is a token amount; it must not land on the kobo schedule
+        client.create_receipt(&1u64, &1_000_000_000, &tenant);
+    }
 
-        // Create receipts for deal 2
-        for i in 1..=3 {
-            env.mock_auths(&[MockAuth {
-                address: &admin,
-                invoke: &MockAuthInvoke {
-                    contract: &contract_id,
-                    fn_name: "create_receipt",
-                    args: (2u64, (i * 2000) as i128, payer.clone()).into_val(&env),
-                    sub_invokes: &[],
-                },
-            }]);
-            client.create_receipt(&2u64, &(i * 2000), &payer);
-        }
+    #[test]
+    fn test_ngn_deal_positions_and_totals_are_in_tokens() {
+        let env = Env::default();
+        let (admin, client, contract_id) = setup(&env);
+        let tenant = Address::generate(&env);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 1, &tenant, 30_000_000, 3, 30 * DAY);
+        create_deal_as_admin(&env, &client, &contract_id, &admin, 2, &tenant, 900, 3, 30 * DAY);
+        let token = create_token(&env, &tenant, 10_000_000_000);
+        let recipient = Some(Address::generate(&env));
+        set_deal_payment_as_admin(&env, &client, &contract_id, &admin, 1, &token, &recipient);
 
-        // Verify deal 1 has 5 receipts
-        let page1 = client.list_receipts_by_deal(&1u64, &10u32, &None);
-        assert_eq!(page1.receipts.len(), 5);
+        env.ledger().set_timestamp(30 * DAY);
+        let oracle = setup_fx_oracle(&env, &[1_500 * FX_RATE_SCALE]);
+        client.set_deal_fx(&1u64, &oracle, &Symbol::new(&env, "NGN_USDC"), &DAY, &1u32);
+        assert_eq!(client.total_outstanding(), 900);
 
-        // Verify deal 2 has 3 receipts
-        let page2 = client.list_receipts_by_deal(&2u64, &10u32, &None);
-        assert_eq!(page2.receipts.len(), 3);
+        let receipt = client.pay(&1u64, &tenant, &10_000_000);
+        let summary = client.deal_summary(&1u64);
+        assert_eq!((summary.total_paid, summary.outstanding), (666_666_667, 20_000_000));
+        assert_eq!(client.tenant_record(&tenant).total_repaid, 666_666_667);
+        assert_eq!(client.total_outstanding(), 900);
+        // ₦300,000 and ₦200,000 at ₦1,500 per USDC
+        let position = client.deal_position(&1u64);
+        assert_eq!((position.principal, position.outstanding), (2_000_000_000, 1_333_333_334));
+
+        let tx_id = BytesN::from_array(&env, &[0xAB; 32]);
+        client.reverse_receipt(&1u64, &receipt.id, &Symbol::new(&env, "refund"), &tx_id);
+        assert_eq!(client.tenant_record(&tenant).total_repaid, 0);
+        assert_eq!(client.deal_summary(&1u64).outstanding, 30_000_000);
+        assert_eq!(client.total_outstanding(), 900);
     }
 }
@@ -0,0 +1,188 @@
+//! Host-side helpers for building receipt inclusion proofs
+//!
+//! Enabled with the `proofs` feature (requires `std`). The contract publishes a
+//! `receipt_leaf` event for every receipt it appends to its Merkle mountain
+//! range; replaying those events into a `ReceiptMmr` reproduces the on-chain
+//! accumulator, from which proofs for `verify_receipt_proof` can be built.
+
+extern crate std;
+
+use sha2::{Digest, Sha256};
+use soroban_sdk::{BytesN, Env, Vec as SorobanVec};
+use std::vec::Vec;
+
+use crate::{locate_leaf, DealId, ReceiptId, ReceiptProof, MMR_NODE_PREFIX, MMR_ROOT_PREFIX};
+
+pub type Hash = [u8; 32];
+
+/// Payload of a `receipt_leaf` event
+///
+/// Topic: `("receipt_leaf", deal_id)`; data: `(receipt_id, leaf_index, leaf_hash)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LeafEvent {
+    pub deal_id: DealId,
+    pub receipt_id: ReceiptId,
+    pub leaf_index: u64,
+    pub leaf_hash: Hash,
+}
+
+/// The event stream did not describe a contiguous run of leaves from 0
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LeafStreamError {
+    Missing(u64),
+    Duplicate(u64),
+}
+
+/// Proof in host types; convert with `to_contract` before calling the contract
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HostProof {
+    pub leaf_count: u64,
+    pub siblings: Vec<Hash>,
+    pub peaks: Vec<Hash>,
+}
+
+impl HostProof {
+    pub fn to_contract(&self, env: &Env) -> ReceiptProof {
+        let mut siblings = SorobanVec::new(env);
+        for sibling in &self.siblings {
+            siblings.push_back(BytesN::from_array(env, sibling));
+        }
+        let mut peaks = SorobanVec::new(env);
+        for peak in &self.peaks {
+            peaks.push_back(BytesN::from_array(env, peak));
+        }
+        ReceiptProof {
+            leaf_count: self.leaf_count,
+            siblings,
+            peaks,
+        }
+    }
+}
+
+/// Off-chain copy of the contract's receipt accumulator
+#[derive(Clone, Debug, Default)]
+pub struct ReceiptMmr {
+    leaves: Vec<Hash>,
+}
+
+impl ReceiptMmr {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rebuild the accumulator from `receipt_leaf` events, in any order
+    pub fn from_events(events: impl IntoIterator<Item = LeafEvent>) -> Result<Self, LeafStreamError> {
+        let mut events: Vec<LeafEvent> = events.into_iter().collect();
+        events.sort_by_key(|e| e.leaf_index);
+
+        let mut mmr = Self::new();
+        for event in events {
+            let expected = mmr.leaf_count();
+            if event.leaf_index < expected {
+                return Err(LeafStreamError::Duplicate(event.leaf_index));
+            }
+            if event.leaf_index > expected {
+                return Err(LeafStreamError::Missing(expected));
+            }
+            mmr.push(event.leaf_hash);
+        }
+        Ok(mmr)
+    }
+
+    pub fn push(&mut self, leaf_hash: Hash) {
+        self.leaves.push(leaf_hash);
+    }
+
+    pub fn leaf_count(&self) -> u64 {
+        self.leaves.len() as u64
+    }
+
+    /// Mountain peaks, tallest first, as stored by the contract
+    pub fn peaks(&self) -> Vec<Hash> {
+        let mut peaks = Vec::new();
+        let mut offset = 0u64;
+        for height in (0..64u32).rev() {
+            if self.leaf_count() & (1u64 << height) != 0 {
+                peaks.push(self.subtree_root(offset, height));
+                offset += 1u64 << height;
+            }
+        }
+        peaks
+    }
+
+    /// Same value as the contract's `receipts_root`
+    pub fn root(&self) -> Hash {
+        bag_peaks(self.leaf_count(), &self.peaks())
+    }
+
+    /// Inclusion proof for the leaf at `leaf_index`, against the current root
+    pub fn proof(&self, leaf_index: u64) -> Option<HostProof> {
+        let (_, height, local_index) = locate_leaf(leaf_index, self.leaf_count())?;
+        let offset = leaf_index - local_index;
+
+        let siblings = (0..height)
+            .map(|level| {
+                let sibling = (local_index >> level) ^ 1;
+                self.subtree_root(offset + (sibling << level), level)
+            })
+            .collect();
+
+        Some(HostProof {
+            leaf_count: self.leaf_count(),
+            siblings,
+            peaks: self.peaks(),
+        })
+    }
+
+    fn subtree_root(&self, start: u64, height: u32) -> Hash {
+        if height == 0 {
+            return self.leaves[start as usize];
+        }
+        let half = 1u64 << (height - 1);
+        hash_node(
+            &self.subtree_root(start, height - 1),
+            &self.subtree_root(start + half, height - 1),
+        )
+    }
+}
+
+/// Check a proof against a root without a contract, e.g. for a third party
+/// holding only `receipts_root` and the leaf hash from a `receipt_leaf` event
+pub fn verify(leaf_hash: &Hash, leaf_index: u64, proof: &HostProof, root: &Hash) -> bool {
+    let Some((position, height, local_index)) = locate_leaf(leaf_index, proof.leaf_count) else {
+        return false;
+    };
+    if proof.siblings.len() != height as usize {
+        return false;
+    }
+
+    let mut node = *leaf_hash;
+    for (level, sibling) in proof.siblings.iter().enumerate() {
+        node = if (local_index >> level) & 1 == 0 {
+            hash_node(&node, sibling)
+        } else {
+            hash_node(sibling, &node)
+        };
+    }
+
+    proof.peaks.get(position as usize) == Some(&node)
+        && bag_peaks(proof.leaf_count, &proof.peaks) == *root
+}
+
+fn hash_node(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([MMR_NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+fn bag_peaks(leaf_count: u64, peaks: &[Hash]) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([MMR_ROOT_PREFIX]);
+    hasher.update(leaf_count.to_be_bytes());
+    for peak in peaks {
+        hasher.update(peak);
+    }
+    hasher.finalize().into()
+}
@@ -1,27 +1,516 @@
 #![no_std]
 
-use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, Map, Symbol};
+use soroban_sdk::{
+    contract, contractimpl, contracttype, vec, Address, BytesN, Env, IntoVal, Map, Symbol, Val,
+    Vec,
+};
+
+/// Denominator for basis-point fees
+pub const BPS_SCALE: i128 = 10_000;
+
+/// Operations that can carry a protocol fee
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeeOp {
+    Debit,
+    Transfer,
+}
+
+/// Protocol fee charged on top of an operation's amount
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FeeRate {
+    Flat(i128),
+    Bps(u32), // Basis points of the amount
+}
+
+/// Default asset funds earmarked for a pending payout
+///
+/// Held funds cannot be debited or transferred until the hold is released,
+/// captured, or reaches `expires_at`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hold {
+    pub hold_id: u64,
+    pub user: Address,
+    pub amount: i128,
+    pub expires_at: u64,
+    pub created_at: u64,
+}
+
+/// What changed a user's balance
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntryKind {
+    Credit,
+    Debit,
+    TransferIn,
+    TransferOut,
+    FeeCollected, // Protocol fee received by the treasury
+}
+
+/// One balance change in a user's journal
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JournalEntry {
+    pub seq: u64, // Position in the user's journal, from 0
+    pub kind: EntryKind,
+    pub asset: Option<Address>, // `None` for the default asset
+    pub amount: i128,
+    pub fee: i128, // Protocol fee paid on top of `amount`
+    pub balance_after: i128,
+    pub counterparty: Option<Address>, // Other side of a transfer, or the fee payer
+    pub timestamp: u64,
+    pub reference: Option<BytesN<32>>,
+}
+
+/// Paginated result for list_entries
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct JournalPage {
+    pub entries: Vec<JournalEntry>,
+    pub has_next: bool,   // True if there are more entries
+    pub next_cursor: u64, // Cursor for next page (only valid if has_next is true)
+}
 
 #[contracttype]
 #[derive(Clone)]
 pub enum DataKey {
     Admin,
-    Balances,
+    Balances, // Default asset balances
     Paused,
+    Treasury,
+    Fee(FeeOp),
+    FeesCollected, // Default asset fees
+    Assets,        // Accepted token contracts
+    DefaultAsset,
+    AssetBalances(Address),
+    AssetFeesCollected(Address),
+    Hold(u64),
+    UserHolds(Address), // Hold ids placed on a user, possibly expired
+    JournalCount(Address),
+    JournalEntry(Address, u64),
+    Reference(BytesN<32>), // Journal entry (user, seq) recorded under an idempotency reference
 }
 
 #[contract]
 pub struct RentWallet;
 
-fn balances(env: &Env) -> Map<Address, i128> {
+// Balances are kept per asset ledger. `None` is the default asset, whose
+// balances stay where the single-asset wallet kept them, so `credit`, `debit`
+// and `transfer` keep working before a default asset is chosen.
+
+fn balances_key(ledger: &Option<Address>) -> DataKey {
+    match ledger {
+        Some(asset) => DataKey::AssetBalances(asset.clone()),
+        None => DataKey::Balances,
+    }
+}
+
+fn fees_key(ledger: &Option<Address>) -> DataKey {
+    match ledger {
+        Some(asset) => DataKey::AssetFeesCollected(asset.clone()),
+        None => DataKey::FeesCollected,
+    }
+}
+
+fn balances(env: &Env, ledger: &Option<Address>) -> Map<Address, i128> {
     env.storage()
         .instance()
-        .get::<_, Map<Address, i128>>(&DataKey::Balances)
+        .get::<_, Map<Address, i128>>(&balances_key(ledger))
         .unwrap_or_else(|| Map::new(env))
 }
 
-fn put_balances(env: &Env, b: Map<Address, i128>) {
-    env.storage().instance().set(&DataKey::Balances, &b)
+fn put_balances(env: &Env, ledger: &Option<Address>, b: Map<Address, i128>) {
+    env.storage().instance().set(&balances_key(ledger), &b)
+}
+
+fn get_assets(env: &Env) -> Vec<Address> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Assets)
+        .unwrap_or_else(|| vec![env])
+}
+
+fn get_default_asset(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&DataKey::DefaultAsset)
+}
+
+/// Ledger holding `asset`'s balances
+fn ledger_of(env: &Env, asset: Address) -> Option<Address> {
+    if get_default_asset(env) == Some(asset.clone()) {
+        None
+    } else {
+        Some(asset)
+    }
+}
+
+/// Ledger for an asset that is being credited or moved, which must be accepted
+fn accepted_ledger(env: &Env, asset: Address) -> Option<Address> {
+    if !get_assets(env).contains(&asset) {
+        panic!("asset not accepted")
+    }
+    ledger_of(env, asset)
+}
+
+/// Publish an event, adding the asset to its topics for non-default assets
+fn publish(
+    env: &Env,
+    ledger: &Option<Address>,
+    mut topics: Vec<Val>,
+    data: impl IntoVal<Env, Val>,
+) {
+    if let Some(asset) = ledger {
+        topics.push_back(asset.into_val(env));
+    }
+    env.events().publish(topics, data);
+}
+
+fn get_hold(env: &Env, hold_id: u64) -> Option<Hold> {
+    env.storage().persistent().get(&DataKey::Hold(hold_id))
+}
+
+/// A user's holds that have not expired
+fn active_holds(env: &Env, user: &Address) -> Vec<Hold> {
+    let now = env.ledger().timestamp();
+    let ids: Vec<u64> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::UserHolds(user.clone()))
+        .unwrap_or_else(|| vec![env]);
+    let mut holds = vec![env];
+    for hold_id in ids.iter() {
+        if let Some(hold) = get_hold(env, hold_id) {
+            if hold.expires_at > now {
+                holds.push_back(hold);
+            }
+        }
+    }
+    holds
+}
+
+/// Rewrite a user's hold index, dropping expired holds
+fn put_holds(env: &Env, user: &Address, holds: &Vec<Hold>) {
+    let ids: Vec<u64> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::UserHolds(user.clone()))
+        .unwrap_or_else(|| vec![env]);
+    for hold_id in ids.iter() {
+        if !holds.iter().any(|h| h.hold_id == hold_id) {
+            env.storage().persistent().remove(&DataKey::Hold(hold_id));
+        }
+    }
+    let mut active = vec![env];
+    for hold in holds.iter() {
+        active.push_back(hold.hold_id);
+    }
+    env.storage()
+        .persistent()
+        .set(&DataKey::UserHolds(user.clone()), &active);
+}
+
+fn held(env: &Env, ledger: &Option<Address>, user: &Address) -> i128 {
+    match ledger {
+        Some(_) => 0,
+        None => active_holds(env, user).iter().map(|h| h.amount).sum(),
+    }
+}
+
+/// An active hold, removed from its user's holds
+fn take_hold(env: &Env, hold_id: u64) -> Hold {
+    let hold = get_hold(env, hold_id).expect("hold not found");
+    if hold.expires_at <= env.ledger().timestamp() {
+        panic!("hold expired")
+    }
+    let mut holds = active_holds(env, &hold.user);
+    let index = holds.iter().position(|h| h.hold_id == hold_id).unwrap();
+    holds.remove(index as u32);
+    put_holds(env, &hold.user, &holds);
+    hold
+}
+
+fn journal_count(env: &Env, user: &Address) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::JournalCount(user.clone()))
+        .unwrap_or(0)
+}
+
+/// Journal entry for a balance change, without a fee, counterparty or reference
+fn entry(
+    env: &Env,
+    kind: EntryKind,
+    ledger: &Option<Address>,
+    amount: i128,
+    balance_after: i128,
+) -> JournalEntry {
+    JournalEntry {
+        seq: 0,
+        kind,
+        asset: ledger.clone(),
+        amount,
+        fee: 0,
+        balance_after,
+        counterparty: None,
+        timestamp: env.ledger().timestamp(),
+        reference: None,
+    }
+}
+
+fn append_entry(env: &Env, user: &Address, mut entry: JournalEntry) -> JournalEntry {
+    let seq = journal_count(env, user);
+    entry.seq = seq;
+    env.storage()
+        .persistent()
+        .set(&DataKey::JournalEntry(user.clone(), seq), &entry);
+    env.storage()
+        .persistent()
+        .set(&DataKey::JournalCount(user.clone()), &(seq + 1));
+    entry
+}
+
+/// The journal entry an operation recorded under `reference`, if one did
+///
+/// Only a retry of the same operation (kind, user, asset, amount and, for
+/// transfers, recipient) may reuse a reference; anything else panics.
+fn referenced_entry(
+    env: &Env,
+    reference: &BytesN<32>,
+    kind: EntryKind,
+    user: &Address,
+    ledger: &Option<Address>,
+    amount: i128,
+    counterparty: Option<&Address>,
+) -> Option<JournalEntry> {
+    let (recorded_user, seq): (Address, u64) = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Reference(reference.clone()))?;
+    let original: JournalEntry = env
+        .storage()
+        .persistent()
+        .get(&DataKey::JournalEntry(recorded_user.clone(), seq))?;
+    if recorded_user != *user
+        || original.kind != kind
+        || original.asset != *ledger
+        || original.amount != amount
+        || original.counterparty.as_ref() != counterparty
+    {
+        panic!("reference already used")
+    }
+    Some(original)
+}
+
+fn record_reference(env: &Env, entry: &JournalEntry, user: &Address) {
+    if let Some(reference) = &entry.reference {
+        env.storage().persistent().set(
+            &DataKey::Reference(reference.clone()),
+            &(user.clone(), entry.seq),
+        );
+    }
+}
+
+fn get_fee(env: &Env, op: FeeOp) -> Option<FeeRate> {
+    env.storage().instance().get(&DataKey::Fee(op))
+}
+
+fn fee_for(env: &Env, op: FeeOp, amount: i128) -> i128 {
+    match get_fee(env, op) {
+        Some(FeeRate::Flat(fee)) => fee,
+        Some(FeeRate::Bps(bps)) => amount * bps as i128 / BPS_SCALE,
+        None => 0,
+    }
+}
+
+fn get_fees_collected(env: &Env, ledger: &Option<Address>) -> i128 {
+    env.storage()
+        .instance()
+        .get(&fees_key(ledger))
+        .unwrap_or(0)
+}
+
+/// Credit `fee`, already taken from `payer`, to the treasury
+fn collect_fee(
+    env: &Env,
+    ledger: &Option<Address>,
+    b: &mut Map<Address, i128>,
+    payer: Address,
+    op: FeeOp,
+    fee: i128,
+) {
+    if fee == 0 {
+        return;
+    }
+    let treasury: Address = env
+        .storage()
+        .instance()
+        .get(&DataKey::Treasury)
+        .expect("no treasury configured");
+    let treasury_balance = b.get(treasury.clone()).unwrap_or(0) + fee;
+    b.set(treasury.clone(), treasury_balance);
+    append_entry(
+        env,
+        &treasury,
+        JournalEntry {
+            counterparty: Some(payer.clone()),
+            ..entry(env, EntryKind::FeeCollected, ledger, fee, treasury_balance)
+        },
+    );
+    env.storage()
+        .instance()
+        .set(&fees_key(ledger), &(get_fees_collected(env, ledger) + fee));
+    publish(
+        env,
+        ledger,
+        (Symbol::new(env, "protocol_fee"), payer).into_val(env),
+        (op, fee, treasury),
+    );
+}
+
+fn credit(
+    env: &Env,
+    ledger: &Option<Address>,
+    user: Address,
+    amount: i128,
+    reference: Option<BytesN<32>>,
+) -> JournalEntry {
+    if amount <= 0 {
+        panic!("amount must be positive")
+    }
+
+    let mut b = balances(env, ledger);
+    let cur = b.get(user.clone()).unwrap_or(0);
+    let new_balance = cur + amount;
+    b.set(user.clone(), new_balance);
+    put_balances(env, ledger, b);
+    let credited = append_entry(
+        env,
+        &user,
+        JournalEntry {
+            reference,
+            ..entry(env, EntryKind::Credit, ledger, amount, new_balance)
+        },
+    );
+    record_reference(env, &credited, &user);
+
+    publish(
+        env,
+        ledger,
+        (Symbol::new(env, "credit"), user).into_val(env),
+        (amount, new_balance),
+    );
+    credited
+}
+
+fn debit(
+    env: &Env,
+    ledger: &Option<Address>,
+    user: Address,
+    amount: i128,
+    reference: Option<BytesN<32>>,
+) -> JournalEntry {
+    let fee = fee_for(env, FeeOp::Debit, amount);
+    debit_with_fee(env, ledger, user, amount, fee, reference)
+}
+
+fn debit_with_fee(
+    env: &Env,
+    ledger: &Option<Address>,
+    user: Address,
+    amount: i128,
+    fee: i128,
+    reference: Option<BytesN<32>>,
+) -> JournalEntry {
+    if amount <= 0 {
+        panic!("amount must be positive")
+    }
+
+    let mut b = balances(env, ledger);
+    let cur = b.get(user.clone()).unwrap_or(0);
+    if cur - held(env, ledger, &user) < amount + fee {
+        panic!("insufficient balance")
+    }
+    let new_balance = cur - amount - fee;
+    b.set(user.clone(), new_balance);
+    let debited = append_entry(
+        env,
+        &user,
+        JournalEntry {
+            fee,
+            reference,
+            ..entry(env, EntryKind::Debit, ledger, amount, new_balance)
+        },
+    );
+    record_reference(env, &debited, &user);
+    collect_fee(env, ledger, &mut b, user.clone(), FeeOp::Debit, fee);
+    put_balances(env, ledger, b);
+
+    publish(
+        env,
+        ledger,
+        (Symbol::new(env, "debit"), user).into_val(env),
+        (amount, new_balance),
+    );
+    debited
+}
+
+/// Returns the sender's journal entry
+fn transfer(
+    env: &Env,
+    ledger: &Option<Address>,
+    from: Address,
+    to: Address,
+    amount: i128,
+    reference: Option<BytesN<32>>,
+) -> JournalEntry {
+    if amount <= 0 {
+        panic!("amount must be positive")
+    }
+    if from == to {
+        panic!("cannot transfer to self")
+    }
+
+    let fee = fee_for(env, FeeOp::Transfer, amount);
+    let mut b = balances(env, ledger);
+    let from_balance = b.get(from.clone()).unwrap_or(0);
+    if from_balance - held(env, ledger, &from) < amount + fee {
+        panic!("insufficient balance")
+    }
+    let from_balance = from_balance - amount - fee;
+    b.set(from.clone(), from_balance);
+    let to_balance = b.get(to.clone()).unwrap_or(0) + amount;
+    b.set(to.clone(), to_balance);
+    let sent = append_entry(
+        env,
+        &from,
+        JournalEntry {
+            fee,
+            counterparty: Some(to.clone()),
+            reference: reference.clone(),
+            ..entry(env, EntryKind::TransferOut, ledger, amount, from_balance)
+        },
+    );
+    record_reference(env, &sent, &from);
+    append_entry(
+        env,
+        &to,
+        JournalEntry {
+            counterparty: Some(from.clone()),
+            reference,
+            ..entry(env, EntryKind::TransferIn, ledger, amount, to_balance)
+        },
+    );
+    collect_fee(env, ledger, &mut b, from.clone(), FeeOp::Transfer, fee);
+    put_balances(env, ledger, b);
+
+    publish(
+        env,
+        ledger,
+        (Symbol::new(env, "transfer"), from, to).into_val(env),
+        (amount, from_balance, to_balance),
+    );
+    sent
 }
 
 fn require_admin(env: &Env) {
@@ -60,48 +549,390 @@ impl RentWallet {
         env.events().publish((Symbol::new(&env, "init"),), admin);
     }
 
+    /// Credit `amount` of the default asset to `user`
     pub fn credit(env: Env, user: Address, amount: i128) {
         require_admin(&env);
         require_not_paused(&env);
-        if amount <= 0 {
-            panic!("amount must be positive")
+        credit(&env, &None, user, amount, None);
+    }
+
+    /// `credit` under an idempotency `reference`, returning its journal entry
+    ///
+    /// The `_ref` variants of `credit`, `debit` and `transfer` (and of their
+    /// asset variants) share one set of references. Repeating a call with a
+    /// reference that was already used does nothing and returns the original
+    /// journal entry; reusing it for a different operation panics.
+    pub fn credit_ref(env: Env, user: Address, amount: i128, reference: BytesN<32>) -> JournalEntry {
+        require_admin(&env);
+        require_not_paused(&env);
+        if let Some(original) =
+            referenced_entry(&env, &reference, EntryKind::Credit, &user, &None, amount, None)
+        {
+            return original;
         }
+        credit(&env, &None, user, amount, Some(reference))
+    }
 
-        let mut b = balances(&env);
-        let cur = b.get(user.clone()).unwrap_or(0);
-        let new_balance = cur + amount;
-        b.set(user.clone(), new_balance);
-        put_balances(&env, b);
+    /// Debit `amount` of the default asset, plus the debit fee if one is set,
+    /// from `user`'s available balance
+    pub fn debit(env: Env, user: Address, amount: i128) {
+        require_admin(&env);
+        require_not_paused(&env);
+        debit(&env, &None, user, amount, None);
+    }
 
-        env.events()
-            .publish((Symbol::new(&env, "credit"), user), (amount, new_balance));
+    /// `debit` under an idempotency `reference` (see `credit_ref`)
+    pub fn debit_ref(env: Env, user: Address, amount: i128, reference: BytesN<32>) -> JournalEntry {
+        require_admin(&env);
+        require_not_paused(&env);
+        if let Some(original) =
+            referenced_entry(&env, &reference, EntryKind::Debit, &user, &None, amount, None)
+        {
+            return original;
+        }
+        debit(&env, &None, user, amount, Some(reference))
     }
 
-    pub fn debit(env: Env, user: Address, amount: i128) {
+    /// Move `amount` of the default asset from `from` to `to`; `from` also pays
+    /// the transfer fee if one is set
+    pub fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+        require_admin(&env);
+        require_not_paused(&env);
+        transfer(&env, &None, from, to, amount, None);
+    }
+
+    /// `transfer` under an idempotency `reference` (see `credit_ref`),
+    /// returning the sender's journal entry
+    pub fn transfer_ref(
+        env: Env,
+        from: Address,
+        to: Address,
+        amount: i128,
+        reference: BytesN<32>,
+    ) -> JournalEntry {
+        require_admin(&env);
+        require_not_paused(&env);
+        if let Some(original) = referenced_entry(
+            &env,
+            &reference,
+            EntryKind::TransferOut,
+            &from,
+            &None,
+            amount,
+            Some(&to),
+        ) {
+            return original;
+        }
+        transfer(&env, &None, from, to, amount, Some(reference))
+    }
+
+    pub fn is_reference_used(env: Env, reference: BytesN<32>) -> bool {
+        env.storage()
+            .persistent()
+            .has(&DataKey::Reference(reference))
+    }
+
+    /// Default asset balance
+    pub fn balance(env: Env, user: Address) -> i128 {
+        let b = balances(&env, &None);
+        b.get(user).unwrap_or(0)
+    }
+
+    /// List a user's journal, oldest first, with cursor-based pagination
+    ///
+    /// Every credit, debit and transfer in any asset appends an entry, as does
+    /// each protocol fee the treasury receives. `limit` must be between 1 and
+    /// 100; `cursor` is the `next_cursor` of the previous page, or `None` to
+    /// start from the first entry.
+    pub fn list_entries(env: Env, user: Address, limit: u32, cursor: Option<u64>) -> JournalPage {
+        if limit == 0 || limit > 100 {
+            panic!("limit must be between 1 and 100")
+        }
+        let count = journal_count(&env, &user);
+        let start = cursor.unwrap_or(0);
+        let end = count.min(start.saturating_add(limit as u64));
+
+        let mut entries = vec![&env];
+        for seq in start..end {
+            let entry: JournalEntry = env
+                .storage()
+                .persistent()
+                .get(&DataKey::JournalEntry(user.clone(), seq))
+                .unwrap();
+            entries.push_back(entry);
+        }
+        JournalPage {
+            entries,
+            has_next: end < count,
+            next_cursor: end,
+        }
+    }
+
+    pub fn entry_count(env: Env, user: Address) -> u64 {
+        journal_count(&env, &user)
+    }
+
+    /// Default asset balance not earmarked by active holds
+    pub fn available_balance(env: Env, user: Address) -> i128 {
+        let b = balances(&env, &None);
+        b.get(user.clone()).unwrap_or(0) - held(&env, &None, &user)
+    }
+
+    /// Earmark `amount` of `user`'s available default asset balance until
+    /// `expires_at`
+    pub fn place_hold(env: Env, user: Address, amount: i128, hold_id: u64, expires_at: u64) {
         require_admin(&env);
         require_not_paused(&env);
         if amount <= 0 {
             panic!("amount must be positive")
         }
+        let now = env.ledger().timestamp();
+        if expires_at <= now {
+            panic!("expires_at must be in the future")
+        }
+        if get_hold(&env, hold_id).is_some() {
+            panic!("hold already exists")
+        }
+        let balance = balances(&env, &None).get(user.clone()).unwrap_or(0);
+        if balance - held(&env, &None, &user) < amount {
+            panic!("insufficient available balance")
+        }
+
+        let hold = Hold {
+            hold_id,
+            user: user.clone(),
+            amount,
+            expires_at,
+            created_at: now,
+        };
+        env.storage().persistent().set(&DataKey::Hold(hold_id), &hold);
+        let mut holds = active_holds(&env, &user);
+        holds.push_back(hold);
+        put_holds(&env, &user, &holds);
+
+        env.events().publish(
+            (Symbol::new(&env, "hold_placed"), user),
+            (hold_id, amount, expires_at),
+        );
+    }
+
+    /// Return a hold's funds to the user's available balance
+    pub fn release_hold(env: Env, hold_id: u64) {
+        require_admin(&env);
+        let hold = take_hold(&env, hold_id);
+        env.events().publish(
+            (Symbol::new(&env, "hold_released"), hold.user),
+            (hold_id, hold.amount),
+        );
+    }
 
-        let mut b = balances(&env);
-        let cur = b.get(user.clone()).unwrap_or(0);
-        if cur < amount {
-            panic!("insufficient balance")
+    /// Debit exactly a hold's funds from the user; no debit fee is charged, as
+    /// it was not held
+    pub fn capture_hold(env: Env, hold_id: u64) {
+        require_admin(&env);
+        require_not_paused(&env);
+        let hold = take_hold(&env, hold_id);
+        debit_with_fee(&env, &None, hold.user.clone(), hold.amount, 0, None);
+        env.events().publish(
+            (Symbol::new(&env, "hold_captured"), hold.user),
+            (hold_id, hold.amount),
+        );
+    }
+
+    /// A hold that has not been released or captured; expired holds are
+    /// dropped when the user's holds next change
+    pub fn get_hold(env: Env, hold_id: u64) -> Option<Hold> {
+        get_hold(&env, hold_id)
+    }
+
+    /// A user's active holds
+    pub fn holds(env: Env, user: Address) -> Vec<Hold> {
+        active_holds(&env, &user)
+    }
+
+    /// Credit `amount` of an accepted `asset` to `user`
+    pub fn credit_asset(env: Env, user: Address, asset: Address, amount: i128) {
+        require_admin(&env);
+        require_not_paused(&env);
+        credit(&env, &accepted_ledger(&env, asset), user, amount, None);
+    }
+
+    /// `credit_asset` under an idempotency `reference` (see `credit_ref`)
+    pub fn credit_asset_ref(
+        env: Env,
+        user: Address,
+        asset: Address,
+        amount: i128,
+        reference: BytesN<32>,
+    ) -> JournalEntry {
+        require_admin(&env);
+        require_not_paused(&env);
+        let ledger = ledger_of(&env, asset.clone());
+        if let Some(original) =
+            referenced_entry(&env, &reference, EntryKind::Credit, &user, &ledger, amount, None)
+        {
+            return original;
         }
-        let new_balance = cur - amount;
-        b.set(user.clone(), new_balance);
-        put_balances(&env, b);
+        credit(&env, &accepted_ledger(&env, asset), user, amount, Some(reference))
+    }
 
-        env.events()
-            .publish((Symbol::new(&env, "debit"), user), (amount, new_balance));
+    /// Debit `amount` of `asset` from `user`; allowed after the asset stops
+    /// being accepted, so remaining balances can be paid out
+    pub fn debit_asset(env: Env, user: Address, asset: Address, amount: i128) {
+        require_admin(&env);
+        require_not_paused(&env);
+        debit(&env, &ledger_of(&env, asset), user, amount, None);
     }
 
-    pub fn balance(env: Env, user: Address) -> i128 {
-        let b = balances(&env);
+    /// `debit_asset` under an idempotency `reference` (see `credit_ref`)
+    pub fn debit_asset_ref(
+        env: Env,
+        user: Address,
+        asset: Address,
+        amount: i128,
+        reference: BytesN<32>,
+    ) -> JournalEntry {
+        require_admin(&env);
+        require_not_paused(&env);
+        let ledger = ledger_of(&env, asset);
+        if let Some(original) =
+            referenced_entry(&env, &reference, EntryKind::Debit, &user, &ledger, amount, None)
+        {
+            return original;
+        }
+        debit(&env, &ledger, user, amount, Some(reference))
+    }
+
+    /// Move `amount` of an accepted `asset` from `from` to `to`
+    pub fn transfer_asset(env: Env, from: Address, to: Address, asset: Address, amount: i128) {
+        require_admin(&env);
+        require_not_paused(&env);
+        transfer(&env, &accepted_ledger(&env, asset), from, to, amount, None);
+    }
+
+    /// `transfer_asset` under an idempotency `reference` (see `credit_ref`)
+    pub fn transfer_asset_ref(
+        env: Env,
+        from: Address,
+        to: Address,
+        asset: Address,
+        amount: i128,
+        reference: BytesN<32>,
+    ) -> JournalEntry {
+        require_admin(&env);
+        require_not_paused(&env);
+        let ledger = ledger_of(&env, asset.clone());
+        if let Some(original) = referenced_entry(
+            &env,
+            &reference,
+            EntryKind::TransferOut,
+            &from,
+            &ledger,
+            amount,
+            Some(&to),
+        ) {
+            return original;
+        }
+        transfer(&env, &accepted_ledger(&env, asset), from, to, amount, Some(reference))
+    }
+
+    pub fn balance_of(env: Env, user: Address, asset: Address) -> i128 {
+        let b = balances(&env, &ledger_of(&env, asset));
         b.get(user).unwrap_or(0)
     }
 
+    /// Accept `asset` for credits and transfers
+    pub fn add_asset(env: Env, asset: Address) {
+        require_admin(&env);
+        let mut assets = get_assets(&env);
+        if assets.contains(&asset) {
+            panic!("asset already accepted")
+        }
+        assets.push_back(asset.clone());
+        env.storage().instance().set(&DataKey::Assets, &assets);
+        env.events().publish((Symbol::new(&env, "add_asset"),), asset);
+    }
+
+    /// Stop accepting `asset`; existing balances can still be debited
+    pub fn remove_asset(env: Env, asset: Address) {
+        require_admin(&env);
+        let mut assets = get_assets(&env);
+        let index = assets.first_index_of(&asset).expect("asset not accepted");
+        assets.remove(index);
+        env.storage().instance().set(&DataKey::Assets, &assets);
+        env.events().publish((Symbol::new(&env, "remove_asset"),), asset);
+    }
+
+    pub fn assets(env: Env) -> Vec<Address> {
+        get_assets(&env)
+    }
+
+    /// Name the token that `credit`, `debit`, `transfer` and `balance` work
+    /// against; balances already held through them become balances of `asset`
+    ///
+    /// Can only be set once, and `asset` must be accepted.
+    pub fn set_default_asset(env: Env, asset: Address) {
+        require_admin(&env);
+        if get_default_asset(&env).is_some() {
+            panic!("default asset already set")
+        }
+        if !get_assets(&env).contains(&asset) {
+            panic!("asset not accepted")
+        }
+        if env.storage().instance().has(&DataKey::AssetBalances(asset.clone())) {
+            panic!("asset already has balances")
+        }
+        env.storage().instance().set(&DataKey::DefaultAsset, &asset);
+        env.events()
+            .publish((Symbol::new(&env, "set_default_asset"),), asset);
+    }
+
+    pub fn default_asset(env: Env) -> Option<Address> {
+        get_default_asset(&env)
+    }
+
+    /// Set the address whose balance receives protocol fees
+    pub fn set_treasury(env: Env, treasury: Address) {
+        require_admin(&env);
+        env.storage().instance().set(&DataKey::Treasury, &treasury);
+        env.events()
+            .publish((Symbol::new(&env, "set_treasury"),), treasury);
+    }
+
+    pub fn treasury(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::Treasury)
+    }
+
+    /// Charge a protocol fee on `op`, or stop charging one with `None`
+    ///
+    /// Fees are charged in the asset of the operation.
+    pub fn set_fee(env: Env, op: FeeOp, fee: Option<FeeRate>) {
+        require_admin(&env);
+        match &fee {
+            Some(FeeRate::Flat(amount)) if *amount < 0 => panic!("fee must not be negative"),
+            Some(FeeRate::Bps(bps)) if *bps as i128 >= BPS_SCALE => {
+                panic!("fee bps must be below 10000")
+            }
+            Some(rate) => env.storage().instance().set(&DataKey::Fee(op), rate),
+            None => env.storage().instance().remove(&DataKey::Fee(op)),
+        }
+        env.events().publish((Symbol::new(&env, "set_fee"), op), fee);
+    }
+
+    pub fn fee(env: Env, op: FeeOp) -> Option<FeeRate> {
+        get_fee(&env, op)
+    }
+
+    /// Protocol fees in the default asset credited to the treasury so far
+    pub fn fees_collected(env: Env) -> i128 {
+        get_fees_collected(&env, &None)
+    }
+
+    /// Protocol fees in `asset` credited to the treasury so far
+    pub fn fees_collected_of(env: Env, asset: Address) -> i128 {
+        get_fees_collected(&env, &ledger_of(&env, asset))
+    }
+
     pub fn set_admin(env: Env, new_admin: Address) {
         require_admin(&env);
         env.storage().instance().set(&DataKey::Admin, &new_admin);
@@ -130,10 +961,11 @@ impl RentWallet {
 mod test {
     extern crate std;
 
-    use super::{RentWallet, RentWalletClient};
-    use soroban_sdk::testutils::{Address as _, Events, MockAuth, MockAuthInvoke};
-    use soroban_sdk::{Address, Env, IntoVal, Symbol, TryIntoVal};
+    use super::{EntryKind, FeeOp, FeeRate, RentWallet, RentWalletClient};
+    use soroban_sdk::testutils::{Address as _, Events, Ledger, MockAuth, MockAuthInvoke};
+    use soroban_sdk::{Address, BytesN, Env, IntoVal, Symbol, TryIntoVal};
 
+    #[allow(deprecated)]
     fn setup(env: &Env) -> (soroban_sdk::Address, RentWalletClient<'_>, Address, Address, Address) {
         let contract_id = env.register_contract(None, RentWallet);
         let client = RentWalletClient::new(env, &contract_id);
@@ -149,6 +981,7 @@ mod test {
     // ============================================================================
 
     #[test]
+    #[allow(deprecated)]
     fn init_sets_admin() {
         let env = Env::default();
         let contract_id = env.register_contract(None, RentWallet);
@@ -173,6 +1006,7 @@ mod test {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn init_initializes_empty_balances() {
         let env = Env::default();
         let contract_id = env.register_contract(None, RentWallet);
@@ -188,6 +1022,7 @@ mod test {
 
     #[test]
     #[should_panic(expected = "already initialized")]
+    #[allow(deprecated)]
     fn init_cannot_be_called_twice() {
         let env = Env::default();
         let contract_id = env.register_contract(None, RentWallet);
@@ -913,4 +1748,538 @@ mod test {
         let data: (i128, i128) = event.2.try_into_val(&env).unwrap();
         assert_eq!(data, (50i128, 150i128));
     }
+
+    // ============================================================================
+    // Transfer Tests
+    // ============================================================================
+
+    #[test]
+    fn transfer_moves_balance() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, other) = setup(&env);
+        env.mock_all_auths();
+        client.credit(&user, &100i128);
+
+        client.transfer(&user, &other, &40i128);
+
+        let event = env.events().all().last().unwrap();
+        assert_eq!(
+            event.1,
+            (Symbol::new(&env, "transfer"), user.clone(), other.clone()).into_val(&env)
+        );
+        let data: (i128, i128, i128) = event.2.try_into_val(&env).unwrap();
+        assert_eq!(data, (40i128, 60i128, 40i128));
+        assert_eq!(client.balance(&user), 60i128);
+        assert_eq!(client.balance(&other), 40i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "insufficient balance")]
+    fn transfer_fails_with_insufficient_balance() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, other) = setup(&env);
+        env.mock_all_auths();
+        client.credit(&user, &10i128);
+
+        client.transfer(&user, &other, &11i128);
+    }
+
+    // ============================================================================
+    // Protocol Fee Tests
+    // ============================================================================
+
+    #[test]
+    fn debit_charges_fee_to_treasury() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, treasury) = setup(&env);
+        env.mock_all_auths();
+        client.set_treasury(&treasury);
+        client.set_fee(&FeeOp::Debit, &Some(FeeRate::Bps(250)));
+        client.credit(&user, &1000i128);
+
+        client.debit(&user, &400i128);
+
+        // The fee is itemized in its own event, ahead of the debit
+        let events = env.events().all();
+        let fee_event = events.get(events.len() - 2).unwrap();
+        assert_eq!(fee_event.1, (Symbol::new(&env, "protocol_fee"), user.clone()).into_val(&env));
+        let data: (FeeOp, i128, Address) = fee_event.2.try_into_val(&env).unwrap();
+        assert_eq!(data, (FeeOp::Debit, 10i128, treasury.clone()));
+
+        assert_eq!(client.balance(&user), 590i128);
+        assert_eq!(client.balance(&treasury), 10i128);
+        assert_eq!(client.fees_collected(), 10i128);
+    }
+
+    #[test]
+    fn transfer_charges_flat_fee_to_sender() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, other) = setup(&env);
+        let treasury = Address::generate(&env);
+        env.mock_all_auths();
+        client.set_treasury(&treasury);
+        client.set_fee(&FeeOp::Transfer, &Some(FeeRate::Flat(5)));
+        client.credit(&user, &100i128);
+
+        client.transfer(&user, &other, &50i128);
+        assert_eq!(client.balance(&user), 45i128);
+        assert_eq!(client.balance(&other), 50i128);
+        assert_eq!(client.balance(&treasury), 5i128);
+
+        // Debits are not charged the transfer fee, and fees can be removed
+        client.debit(&other, &50i128);
+        client.set_fee(&FeeOp::Transfer, &None);
+        client.transfer(&user, &other, &45i128);
+        assert_eq!(client.balance(&user), 0i128);
+        assert_eq!(client.fees_collected(), 5i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "insufficient balance")]
+    fn debit_fails_when_fee_exceeds_balance() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, treasury) = setup(&env);
+        env.mock_all_auths();
+        client.set_treasury(&treasury);
+        client.set_fee(&FeeOp::Debit, &Some(FeeRate::Flat(1)));
+        client.credit(&user, &100i128);
+
+        client.debit(&user, &100i128);
+    }
+
+    // ============================================================================
+    // Multi-Asset Tests
+    // ============================================================================
+
+    #[test]
+    fn assets_have_separate_balances() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, other) = setup(&env);
+        let usdc = Address::generate(&env);
+        let local = Address::generate(&env);
+        env.mock_all_auths();
+        client.add_asset(&usdc);
+        client.add_asset(&local);
+
+        client.credit_asset(&user, &usdc, &100i128);
+        client.credit_asset(&user, &local, &500i128);
+        client.transfer_asset(&user, &other, &local, &200i128);
+        client.debit_asset(&user, &usdc, &30i128);
+
+        assert_eq!(client.balance_of(&user, &usdc), 70i128);
+        assert_eq!(client.balance_of(&user, &local), 300i128);
+        assert_eq!(client.balance_of(&other, &local), 200i128);
+        assert_eq!(client.balance_of(&other, &usdc), 0i128);
+        // Without a default asset, the single-asset balance is untouched
+        assert_eq!(client.balance(&user), 0i128);
+    }
+
+    #[test]
+    fn asset_events_name_the_asset() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, _other) = setup(&env);
+        let usdc = Address::generate(&env);
+        env.mock_all_auths();
+        client.add_asset(&usdc);
+
+        client.credit_asset(&user, &usdc, &100i128);
+
+        let event = env.events().all().last().unwrap();
+        assert_eq!(
+            event.1,
+            (Symbol::new(&env, "credit"), user.clone(), usdc.clone()).into_val(&env)
+        );
+        let data: (i128, i128) = event.2.try_into_val(&env).unwrap();
+        assert_eq!(data, (100i128, 100i128));
+    }
+
+    #[test]
+    fn default_asset_backs_single_asset_methods() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, other) = setup(&env);
+        let usdc = Address::generate(&env);
+        env.mock_all_auths();
+        client.credit(&user, &100i128);
+
+        client.add_asset(&usdc);
+        client.set_default_asset(&usdc);
+        assert_eq!(client.default_asset(), Some(usdc.clone()));
+        assert_eq!(client.balance_of(&user, &usdc), 100i128);
+
+        client.credit_asset(&user, &usdc, &50i128);
+        client.transfer(&user, &other, &25i128);
+        assert_eq!(client.balance(&user), 125i128);
+        assert_eq!(client.balance_of(&other, &usdc), 25i128);
+    }
+
+    #[test]
+    fn fees_are_charged_in_the_operation_asset() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, treasury) = setup(&env);
+        let local = Address::generate(&env);
+        env.mock_all_auths();
+        client.add_asset(&local);
+        client.set_treasury(&treasury);
+        client.set_fee(&FeeOp::Debit, &Some(FeeRate::Flat(5)));
+        client.credit_asset(&user, &local, &100i128);
+
+        client.debit_asset(&user, &local, &50i128);
+
+        assert_eq!(client.balance_of(&user, &local), 45i128);
+        assert_eq!(client.balance_of(&treasury, &local), 5i128);
+        assert_eq!(client.fees_collected_of(&local), 5i128);
+        assert_eq!(client.fees_collected(), 0i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "asset not accepted")]
+    fn credit_asset_requires_accepted_asset() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, _other) = setup(&env);
+        env.mock_all_auths();
+
+        client.credit_asset(&user, &Address::generate(&env), &100i128);
+    }
+
+    #[test]
+    fn removed_asset_can_still_be_debited() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, _other) = setup(&env);
+        let local = Address::generate(&env);
+        env.mock_all_auths();
+        client.add_asset(&local);
+        client.credit_asset(&user, &local, &100i128);
+
+        client.remove_asset(&local);
+        assert_eq!(client.assets().len(), 0);
+        client.debit_asset(&user, &local, &100i128);
+        assert_eq!(client.balance_of(&user, &local), 0i128);
+    }
+
+    // ============================================================================
+    // Hold Tests
+    // ============================================================================
+
+    #[test]
+    fn hold_reduces_available_balance() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, _other) = setup(&env);
+        env.mock_all_auths();
+        client.credit(&user, &100i128);
+
+        client.place_hold(&user, &60i128, &1u64, &1000u64);
+
+        assert_eq!(client.balance(&user), 100i128);
+        assert_eq!(client.available_balance(&user), 40i128);
+        assert_eq!(client.holds(&user).len(), 1);
+        client.debit(&user, &40i128);
+        assert_eq!(client.available_balance(&user), 0i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "insufficient balance")]
+    fn debit_cannot_spend_held_funds() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, _other) = setup(&env);
+        env.mock_all_auths();
+        client.credit(&user, &100i128);
+        client.place_hold(&user, &60i128, &1u64, &1000u64);
+
+        client.debit(&user, &41i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "insufficient balance")]
+    fn transfer_cannot_spend_held_funds() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, other) = setup(&env);
+        env.mock_all_auths();
+        client.credit(&user, &100i128);
+        client.place_hold(&user, &60i128, &1u64, &1000u64);
+
+        client.transfer(&user, &other, &41i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "insufficient available balance")]
+    fn holds_cannot_exceed_available_balance() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, _other) = setup(&env);
+        env.mock_all_auths();
+        client.credit(&user, &100i128);
+        client.place_hold(&user, &60i128, &1u64, &1000u64);
+
+        client.place_hold(&user, &41i128, &2u64, &1000u64);
+    }
+
+    #[test]
+    fn capture_hold_debits_held_funds() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, _other) = setup(&env);
+        env.mock_all_auths();
+        client.credit(&user, &100i128);
+        client.place_hold(&user, &60i128, &1u64, &1000u64);
+
+        client.capture_hold(&1u64);
+
+        let event = env.events().all().last().unwrap();
+        assert_eq!(event.1, (Symbol::new(&env, "hold_captured"), user.clone()).into_val(&env));
+        assert_eq!(client.balance(&user), 40i128);
+        assert_eq!(client.available_balance(&user), 40i128);
+        assert_eq!(client.get_hold(&1u64), None);
+    }
+
+    #[test]
+    fn capture_hold_charges_no_debit_fee() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, treasury) = setup(&env);
+        env.mock_all_auths();
+        client.set_treasury(&treasury);
+        client.set_fee(&FeeOp::Debit, &Some(FeeRate::Flat(5)));
+        client.credit(&user, &100i128);
+        client.place_hold(&user, &100i128, &1u64, &1000u64);
+
+        client.capture_hold(&1u64);
+
+        assert_eq!(client.balance(&user), 0i128);
+        assert_eq!(client.balance(&treasury), 0i128);
+        assert_eq!(client.get_hold(&1u64), None);
+    }
+
+    #[test]
+    fn release_hold_frees_funds() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, _other) = setup(&env);
+        env.mock_all_auths();
+        client.credit(&user, &100i128);
+        client.place_hold(&user, &60i128, &1u64, &1000u64);
+
+        client.release_hold(&1u64);
+
+        assert_eq!(client.balance(&user), 100i128);
+        assert_eq!(client.available_balance(&user), 100i128);
+        assert_eq!(client.holds(&user).len(), 0);
+    }
+
+    #[test]
+    fn holds_expire_by_ledger_timestamp() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, _other) = setup(&env);
+        env.mock_all_auths();
+        client.credit(&user, &100i128);
+        client.place_hold(&user, &60i128, &1u64, &1000u64);
+
+        env.ledger().set_timestamp(1000);
+
+        assert_eq!(client.available_balance(&user), 100i128);
+        assert_eq!(client.holds(&user).len(), 0);
+        client.debit(&user, &100i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "hold expired")]
+    fn expired_hold_cannot_be_captured() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, _other) = setup(&env);
+        env.mock_all_auths();
+        client.credit(&user, &100i128);
+        client.place_hold(&user, &60i128, &1u64, &1000u64);
+
+        env.ledger().set_timestamp(1000);
+        client.capture_hold(&1u64);
+    }
+
+    // ============================================================================
+    // Journal Tests
+    // ============================================================================
+
+    #[test]
+    fn journal_records_every_balance_change() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, other) = setup(&env);
+        let treasury = Address::generate(&env);
+        env.mock_all_auths();
+        client.set_treasury(&treasury);
+        client.set_fee(&FeeOp::Transfer, &Some(FeeRate::Flat(2)));
+
+        client.credit(&user, &100i128);
+        client.transfer(&user, &other, &30i128);
+        client.debit(&user, &8i128);
+
+        let entries = client.list_entries(&user, &10u32, &None).entries;
+        assert_eq!(entries.len(), 3);
+        let credit = entries.get_unchecked(0);
+        assert_eq!((credit.seq, credit.kind, credit.balance_after), (0, EntryKind::Credit, 100));
+        let sent = entries.get_unchecked(1);
+        assert_eq!((sent.kind, sent.amount, sent.fee), (EntryKind::TransferOut, 30, 2));
+        assert_eq!(sent.balance_after, 68);
+        assert_eq!(sent.counterparty, Some(other.clone()));
+        let debit = entries.get_unchecked(2);
+        assert_eq!((debit.kind, debit.amount, debit.balance_after), (EntryKind::Debit, 8, 60));
+        assert_eq!(debit.asset, None);
+
+        let received = client.list_entries(&other, &10u32, &None).entries.get_unchecked(0);
+        assert_eq!((received.kind, received.amount, received.balance_after), (EntryKind::TransferIn, 30, 30));
+        assert_eq!(received.counterparty, Some(user.clone()));
+
+        let fee = client.list_entries(&treasury, &10u32, &None).entries.get_unchecked(0);
+        assert_eq!((fee.kind, fee.amount, fee.balance_after), (EntryKind::FeeCollected, 2, 2));
+        assert_eq!(fee.counterparty, Some(user));
+    }
+
+    #[test]
+    fn journal_records_asset_entries() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, _other) = setup(&env);
+        let usdc = Address::generate(&env);
+        env.mock_all_auths();
+        client.add_asset(&usdc);
+
+        client.credit_asset(&user, &usdc, &100i128);
+
+        let entry = client.list_entries(&user, &10u32, &None).entries.get_unchecked(0);
+        assert_eq!(entry.asset, Some(usdc));
+        assert_eq!(entry.balance_after, 100);
+    }
+
+    #[test]
+    fn list_entries_paginates() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, _other) = setup(&env);
+        env.mock_all_auths();
+        for amount in 1..=5 {
+            client.credit(&user, &(amount as i128));
+        }
+        assert_eq!(client.entry_count(&user), 5);
+
+        let page1 = client.list_entries(&user, &2u32, &None);
+        assert_eq!(page1.entries.len(), 2);
+        assert!(page1.has_next);
+
+        let page2 = client.list_entries(&user, &2u32, &Some(page1.next_cursor));
+        assert_eq!(page2.entries.get_unchecked(0).amount, 3);
+        assert!(page2.has_next);
+
+        let page3 = client.list_entries(&user, &2u32, &Some(page2.next_cursor));
+        assert_eq!(page3.entries.len(), 1);
+        assert_eq!(page3.entries.get_unchecked(0).balance_after, 15);
+        assert!(!page3.has_next);
+    }
+
+    #[test]
+    fn list_entries_past_the_end_is_empty() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, _other) = setup(&env);
+        env.mock_all_auths();
+        client.credit(&user, &1i128);
+
+        let page = client.list_entries(&user, &100u32, &Some(u64::MAX));
+        assert_eq!(page.entries.len(), 0);
+        assert!(!page.has_next);
+    }
+
+    #[test]
+    #[should_panic(expected = "limit must be between 1 and 100")]
+    fn list_entries_rejects_zero_limit() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, _other) = setup(&env);
+
+        client.list_entries(&user, &0u32, &None);
+    }
+
+    // ============================================================================
+    // Idempotency Tests
+    // ============================================================================
+
+    #[test]
+    fn repeated_credit_reference_is_a_no_op() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, _other) = setup(&env);
+        let reference = BytesN::from_array(&env, &[7u8; 32]);
+        env.mock_all_auths();
+
+        let first = client.credit_ref(&user, &100i128, &reference);
+        let retry = client.credit_ref(&user, &100i128, &reference);
+
+        assert_eq!(retry, first);
+        assert_eq!(first.reference, Some(reference.clone()));
+        assert_eq!(client.balance(&user), 100i128);
+        assert_eq!(client.entry_count(&user), 1);
+        assert!(client.is_reference_used(&reference));
+    }
+
+    #[test]
+    fn repeated_debit_and_transfer_references_are_no_ops() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, other) = setup(&env);
+        let debit_ref = BytesN::from_array(&env, &[1u8; 32]);
+        let transfer_ref = BytesN::from_array(&env, &[2u8; 32]);
+        env.mock_all_auths();
+        client.credit(&user, &100i128);
+
+        let debited = client.debit_ref(&user, &30i128, &debit_ref);
+        assert_eq!(client.debit_ref(&user, &30i128, &debit_ref), debited);
+        let sent = client.transfer_ref(&user, &other, &20i128, &transfer_ref);
+        assert_eq!(client.transfer_ref(&user, &other, &20i128, &transfer_ref), sent);
+
+        assert_eq!(sent.balance_after, 50i128);
+        assert_eq!(client.balance(&user), 50i128);
+        assert_eq!(client.balance(&other), 20i128);
+    }
+
+    #[test]
+    fn repeated_asset_references_are_no_ops() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, other) = setup(&env);
+        let usdc = Address::generate(&env);
+        env.mock_all_auths();
+        client.add_asset(&usdc);
+        let credit_ref = BytesN::from_array(&env, &[4u8; 32]);
+        let debit_ref = BytesN::from_array(&env, &[5u8; 32]);
+        let transfer_ref = BytesN::from_array(&env, &[6u8; 32]);
+
+        for _ in 0..2 {
+            client.credit_asset_ref(&user, &usdc, &100i128, &credit_ref);
+            client.debit_asset_ref(&user, &usdc, &30i128, &debit_ref);
+            client.transfer_asset_ref(&user, &other, &usdc, &20i128, &transfer_ref);
+        }
+
+        assert_eq!(client.balance_of(&user, &usdc), 50i128);
+        assert_eq!(client.balance_of(&other, &usdc), 20i128);
+    }
+
+    #[test]
+    #[should_panic(expected = "reference already used")]
+    fn references_are_shared_across_operations() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, _other) = setup(&env);
+        let reference = BytesN::from_array(&env, &[3u8; 32]);
+        env.mock_all_auths();
+
+        client.credit_ref(&user, &100i128, &reference);
+        // The reference is spent, so a debit cannot reuse it
+        client.debit_ref(&user, &50i128, &reference);
+    }
+
+    #[test]
+    #[should_panic(expected = "reference already used")]
+    fn reference_retry_must_match_the_original_amount() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, _other) = setup(&env);
+        let reference = BytesN::from_array(&env, &[3u8; 32]);
+        env.mock_all_auths();
+
+        client.credit_ref(&user, &100i128, &reference);
+        client.credit_ref(&user, &200i128, &reference);
+    }
+
+    #[test]
+    fn unused_reference_is_not_reported_as_used() {
+        let env = Env::default();
+        let (_contract_id, client, _admin, user, _other) = setup(&env);
+        env.mock_all_auths();
+        client.credit(&user, &100i128);
+
+        assert!(!client.is_reference_used(&BytesN::from_array(&env, &[9u8; 32])));
+    }
 }
@@ -0,0 +1,353 @@
+#![no_std]
+
+use soroban_sdk::{
+    contract, contractclient, contractimpl, contracttype, token, Address, Env, Symbol,
+};
+
+/// Basis-point scale for `coverage_ratio`
+pub const BPS_SCALE: i128 = 10_000;
+
+/// Deal lifecycle as reported by rent_payments
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DealStatus {
+    Active,
+    Completed,
+    Defaulted,
+}
+
+/// rent_payments' `DealPosition`
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DealPosition {
+    pub status: DealStatus,
+    pub landlord: Address,
+    pub principal: i128,
+    pub outstanding: i128,
+}
+
+/// A payout made to cover a defaulted deal
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Claim {
+    pub deal_id: u64,
+    pub beneficiary: Address,
+    pub outstanding: i128, // Principal owed when the deal defaulted
+    pub amount: i128,      // Paid out; less than `outstanding` if the reserve ran short
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Admin,
+    Token,
+    DealsContract,
+    Beneficiary,
+    Claim(u64), // Claim paid for a deal
+}
+
+/// The part of rent_payments the reserve reads
+#[contractclient(name = "DealsClient")]
+pub trait DealsInterface {
+    fn deal_position(env: Env, deal_id: u64) -> DealPosition;
+    fn total_outstanding(env: Env) -> i128;
+}
+
+#[contract]
+pub struct Reserve;
+
+fn require_admin(env: &Env) {
+    let admin: Address = env
+        .storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .expect("admin not set");
+    admin.require_auth();
+}
+
+fn token_client(env: &Env) -> token::Client<'_> {
+    let token: Address = env.storage().instance().get(&DataKey::Token).unwrap();
+    token::Client::new(env, &token)
+}
+
+fn deals_client(env: &Env) -> DealsClient<'_> {
+    let deals: Address = env.storage().instance().get(&DataKey::DealsContract).unwrap();
+    DealsClient::new(env, &deals)
+}
+
+fn balance(env: &Env) -> i128 {
+    token_client(env).balance(&env.current_contract_address())
+}
+
+fn get_claim(env: &Env, deal_id: u64) -> Option<Claim> {
+    env.storage().persistent().get(&DataKey::Claim(deal_id))
+}
+
+#[contractimpl]
+impl Reserve {
+    /// `token` is the reserve asset, `deals_contract` the rent_payments
+    /// contract that sends the reserve its cut of repayments, and
+    /// `beneficiary` the funding source compensated for defaults (e.g. the
+    /// funding pool)
+    pub fn init(
+        env: Env,
+        admin: Address,
+        token: Address,
+        deals_contract: Address,
+        beneficiary: Address,
+    ) {
+        if env.storage().instance().has(&DataKey::Admin) {
+            panic!("already initialized")
+        }
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::Token, &token);
+        env.storage()
+            .instance()
+            .set(&DataKey::DealsContract, &deals_contract);
+        env.storage()
+            .instance()
+            .set(&DataKey::Beneficiary, &beneficiary);
+        env.events().publish((Symbol::new(&env, "init"),), admin);
+    }
+
+    /// Change who is compensated for future defaults (admin-auth)
+    pub fn set_beneficiary(env: Env, beneficiary: Address) {
+        require_admin(&env);
+        env.storage()
+            .instance()
+            .set(&DataKey::Beneficiary, &beneficiary);
+        env.events()
+            .publish((Symbol::new(&env, "set_beneficiary"),), beneficiary);
+    }
+
+    /// Compensate the beneficiary for a defaulted deal (admin-auth)
+    ///
+    /// Pays the principal still owed when the deal defaulted, or whatever the
+    /// reserve holds if that is less; NGN-denominated deals are valued at the
+    /// oracle's current rate. Each deal can be covered once.
+    pub fn cover_default(env: Env, deal_id: u64) -> Claim {
+        require_admin(&env);
+        if get_claim(&env, deal_id).is_some() {
+            panic!("default already covered")
+        }
+        let position = deals_client(&env).deal_position(&deal_id);
+        if position.status != DealStatus::Defaulted {
+            panic!("deal is not defaulted")
+        }
+
+        let amount = position.outstanding.min(balance(&env));
+        let beneficiary: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Beneficiary)
+            .unwrap();
+        if amount > 0 {
+            token_client(&env).transfer(&env.current_contract_address(), &beneficiary, &amount);
+        }
+
+        let claim = Claim {
+            deal_id,
+            beneficiary: beneficiary.clone(),
+            outstanding: position.outstanding,
+            amount,
+            timestamp: env.ledger().timestamp(),
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::Claim(deal_id), &claim);
+        env.events().publish(
+            (Symbol::new(&env, "default_covered"), deal_id),
+            (beneficiary, position.outstanding, amount),
+        );
+        claim
+    }
+
+    pub fn balance(env: Env) -> i128 {
+        balance(&env)
+    }
+
+    /// Reserve balance as a share of unpaid principal on active deals, in
+    /// basis points; `None` while nothing is outstanding. NGN-denominated deals
+    /// are not counted (see rent_payments' `total_outstanding`)
+    pub fn coverage_ratio(env: Env) -> Option<i128> {
+        let outstanding = deals_client(&env).total_outstanding();
+        if outstanding <= 0 {
+            return None;
+        }
+        Some(balance(&env) * BPS_SCALE / outstanding)
+    }
+
+    /// The claim paid for a deal, if its default was covered
+    pub fn get_claim(env: Env, deal_id: u64) -> Option<Claim> {
+        get_claim(&env, deal_id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::{Reserve, ReserveClient};
+    use rent_payments::RentPaymentsClient;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::token::{Client as TokenClient, StellarAssetClient};
+    use soroban_sdk::{Address, Env, Symbol};
+
+    const DAY: u64 = 24 * 60 * 60;
+
+    /// Deal 1 (1200 over 12 installments) repaid to a funder, with 10% of every
+    /// repayment going to the reserve; returns the reserve, rent_payments, the
+    /// token, the tenant and the funder
+    fn setup(
+        env: &Env,
+    ) -> (ReserveClient<'_>, RentPaymentsClient<'_>, TokenClient<'_>, Address, Address) {
+        let admin = Address::generate(env);
+        let token_id = env
+            .register_stellar_asset_contract_v2(Address::generate(env))
+            .address();
+        let deals_id = env.register(rent_payments::RentPayments, ());
+        let reserve_id = env.register(Reserve, ());
+        let deals = RentPaymentsClient::new(env, &deals_id);
+        let reserve = ReserveClient::new(env, &reserve_id);
+        let tenant = Address::generate(env);
+        let funder = Address::generate(env);
+
+        env.mock_all_auths();
+        deals.init(&admin);
+        deals.create_deal(&1u64, &tenant, &Address::generate(env), &1200, &12, &(30 * DAY));
+        deals.set_deal_payment(&1u64, &token_id, &Some(funder.clone()));
+        deals.set_reserve(&reserve_id, &1000u32);
+        reserve.init(&admin, &token_id, &deals_id, &funder);
+
+        StellarAssetClient::new(env, &token_id).mint(&tenant, &2000);
+        (reserve, deals, TokenClient::new(env, &token_id), tenant, funder)
+    }
+
+    /// An oracle quoting ₦1,500 per token for `NGN_USDC`
+    fn ngn_oracle(env: &Env) -> Address {
+        let oracle_id = env.register(fx_oracle::FxOracle, ());
+        let oracle = fx_oracle::FxOracleClient::new(env, &oracle_id);
+        let reporter = Address::generate(env);
+        oracle.init(&Address::generate(env));
+        oracle.add_reporter(&reporter);
+        let rate = 1_500 * fx_oracle::RATE_SCALE;
+        let pair = Symbol::new(env, "NGN_USDC");
+        oracle.submit_rate(&reporter, &pair, &rate, &env.ledger().timestamp());
+        oracle_id
+    }
+
+    // ============================================================================
+    // Funding Tests
+    // ============================================================================
+
+    #[test]
+    fn repayments_fund_reserve() {
+        let env = Env::default();
+        let (reserve, deals, token, tenant, funder) = setup(&env);
+
+        env.ledger().set_timestamp(30 * DAY);
+        deals.pay(&1u64, &tenant, &300);
+
+        assert_eq!(reserve.balance(), 30);
+        assert_eq!(token.balance(&reserve.address), 30);
+        assert_eq!(token.balance(&funder), 270);
+        assert_eq!(deals.deal_position(&1u64).outstanding, 900);
+    }
+
+    #[test]
+    fn coverage_ratio_tracks_outstanding() {
+        let env = Env::default();
+        let (reserve, deals, _token, tenant, _funder) = setup(&env);
+        assert_eq!(reserve.coverage_ratio(), Some(0));
+
+        deals.pay(&1u64, &tenant, &400);
+        // 40 held against 800 outstanding
+        assert_eq!(reserve.coverage_ratio(), Some(500));
+
+        deals.pay(&1u64, &tenant, &800);
+        assert_eq!(reserve.coverage_ratio(), None);
+    }
+
+    // ============================================================================
+    // Claim Tests
+    // ============================================================================
+
+    #[test]
+    fn cover_default_pays_funder_up_to_outstanding() {
+        let env = Env::default();
+        let (reserve, deals, token, tenant, funder) = setup(&env);
+        deals.pay(&1u64, &tenant, &1100);
+        deals.mark_default(&1u64);
+        env.ledger().set_timestamp(90 * DAY);
+
+        let claim = reserve.cover_default(&1u64);
+
+        // 110 held, only 100 owed
+        assert_eq!(claim.outstanding, 100);
+        assert_eq!(claim.amount, 100);
+        assert_eq!(claim.beneficiary, funder);
+        assert_eq!(claim.timestamp, 90 * DAY);
+        assert_eq!(reserve.balance(), 10);
+        assert_eq!(token.balance(&funder), 990 + 100);
+        assert_eq!(reserve.get_claim(&1u64), Some(claim));
+    }
+
+    #[test]
+    fn cover_default_pays_ngn_deals_in_tokens() {
+        let env = Env::default();
+        let (reserve, deals, token, tenant, funder) = setup(&env);
+        // ₦3,000 over 3 installments, in kobo
+        deals.create_deal(&2u64, &tenant, &Address::generate(&env), &300_000, &3, &(30 * DAY));
+        deals.set_deal_payment(&2u64, &token.address, &Some(funder.clone()));
+        let pair = Symbol::new(&env, "NGN_USDC");
+        deals.set_deal_fx(&2u64, &ngn_oracle(&env), &pair, &DAY, &1u32);
+        // Only deal 1 counts towards the coverage ratio
+        StellarAssetClient::new(&env, &token.address).mint(&reserve.address, &20_000_000);
+        assert_eq!(reserve.coverage_ratio(), Some(20_000_000 * 10_000 / 1200));
+
+        deals.mark_default(&2u64);
+        let claim = reserve.cover_default(&2u64);
+
+        // ₦3,000 at ₦1,500 per token
+        assert_eq!(claim.outstanding, 20_000_000);
+        assert_eq!(claim.amount, 20_000_000);
+        assert_eq!(token.balance(&funder), 20_000_000);
+    }
+
+    #[test]
+    fn cover_default_is_capped_by_balance() {
+        let env = Env::default();
+        let (reserve, deals, _token, tenant, _funder) = setup(&env);
+        deals.pay(&1u64, &tenant, &200);
+        deals.mark_default(&1u64);
+
+        let claim = reserve.cover_default(&1u64);
+
+        assert_eq!(claim.outstanding, 1000);
+        assert_eq!(claim.amount, 20);
+        assert_eq!(reserve.balance(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "default already covered")]
+    fn cover_default_rejects_second_claim() {
+        let env = Env::default();
+        let (reserve, deals, _token, tenant, _funder) = setup(&env);
+        deals.pay(&1u64, &tenant, &200);
+        deals.mark_default(&1u64);
+
+        reserve.cover_default(&1u64);
+        reserve.cover_default(&1u64);
+    }
+
+    #[test]
+    #[should_panic(expected = "deal is not defaulted")]
+    fn cover_default_requires_default() {
+        let env = Env::default();
+        let (reserve, deals, _token, tenant, _funder) = setup(&env);
+        deals.pay(&1u64, &tenant, &200);
+
+        reserve.cover_default(&1u64);
+    }
+}
@@ -0,0 +1,496 @@
+#![no_std]
+
+use soroban_sdk::{contract, contractclient, contractimpl, contracttype, token, Address, Env, Symbol};
+
+/// Reward ID type
+pub type RewardId = u64;
+
+/// Timestamp type (Unix timestamp in seconds)
+pub type Timestamp = u64;
+
+/// Lifecycle of a reward; mirrors the backend's `RewardStatus`
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RewardStatus {
+    Pending,
+    Payable,
+    Paid,
+    Cancelled,
+}
+
+/// Deal lifecycle as reported by rent_payments' `deal_status`
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DealStatus {
+    Active,
+    Completed,
+    Defaulted,
+}
+
+/// A whistleblower's reward for a listing that led to a deal
+///
+/// `amount` is held in escrow from the reward pool from creation until the
+/// reward is claimed or cancelled.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Reward {
+    pub id: RewardId,
+    pub whistleblower: Address,
+    pub deal_id: u64,
+    pub listing_id: u64,
+    pub amount: i128,
+    pub status: RewardStatus,
+    pub created_at: Timestamp,
+    pub updated_at: Timestamp,
+    pub paid_at: Option<Timestamp>,
+    pub cancel_reason: Option<Symbol>, // Set when cancelled
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Admin,
+    Token,
+    DealsContract,
+    Pool, // Funded but not yet escrowed for a reward
+    RewardCount,
+    Reward(RewardId),
+}
+
+/// The part of rent_payments that rewards read
+#[contractclient(name = "DealsClient")]
+pub trait DealsInterface {
+    fn deal_status(env: Env, deal_id: u64) -> DealStatus;
+    fn deal_listing(env: Env, deal_id: u64) -> Option<u64>;
+}
+
+#[contract]
+pub struct Rewards;
+
+fn require_admin(env: &Env) {
+    let admin: Address = env
+        .storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .expect("admin not set");
+    admin.require_auth();
+}
+
+fn token_client(env: &Env) -> token::Client<'_> {
+    let token: Address = env.storage().instance().get(&DataKey::Token).unwrap();
+    token::Client::new(env, &token)
+}
+
+fn get_pool(env: &Env) -> i128 {
+    env.storage().instance().get(&DataKey::Pool).unwrap_or(0)
+}
+
+fn put_pool(env: &Env, pool: i128) {
+    env.storage().instance().set(&DataKey::Pool, &pool)
+}
+
+fn get_reward(env: &Env, reward_id: RewardId) -> Reward {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Reward(reward_id))
+        .expect("reward not found")
+}
+
+fn put_reward(env: &Env, reward: &Reward) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::Reward(reward.id), reward);
+}
+
+#[contractimpl]
+impl Rewards {
+    /// `token` is the reward asset (e.g. USDC) and `deals_contract` the
+    /// rent_payments contract whose deals rewards are tied to
+    pub fn init(env: Env, admin: Address, token: Address, deals_contract: Address) {
+        if env.storage().instance().has(&DataKey::Admin) {
+            panic!("already initialized")
+        }
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::Token, &token);
+        env.storage()
+            .instance()
+            .set(&DataKey::DealsContract, &deals_contract);
+        env.events().publish((Symbol::new(&env, "init"),), admin);
+    }
+
+    /// Add funds to the reward pool (funder-auth)
+    pub fn fund_pool(env: Env, from: Address, amount: i128) {
+        from.require_auth();
+        if amount <= 0 {
+            panic!("amount must be positive")
+        }
+        token_client(&env).transfer(&from, &env.current_contract_address(), &amount);
+        let pool = get_pool(&env) + amount;
+        put_pool(&env, pool);
+        env.events()
+            .publish((Symbol::new(&env, "pool_funded"), from), (amount, pool));
+    }
+
+    /// Take unescrowed funds back out of the reward pool (admin-auth)
+    pub fn withdraw_pool(env: Env, to: Address, amount: i128) {
+        require_admin(&env);
+        if amount <= 0 {
+            panic!("amount must be positive")
+        }
+        let pool = get_pool(&env);
+        if amount > pool {
+            panic!("insufficient pool balance")
+        }
+        put_pool(&env, pool - amount);
+        token_client(&env).transfer(&env.current_contract_address(), &to, &amount);
+        env.events()
+            .publish((Symbol::new(&env, "pool_withdrawn"), to), (amount, pool - amount));
+    }
+
+    /// Create a pending reward, escrowing `amount` from the pool (admin-auth)
+    pub fn create_reward(
+        env: Env,
+        whistleblower: Address,
+        deal_id: u64,
+        listing_id: u64,
+        amount: i128,
+    ) -> RewardId {
+        require_admin(&env);
+        if amount <= 0 {
+            panic!("amount must be positive")
+        }
+        let pool = get_pool(&env);
+        if amount > pool {
+            panic!("insufficient pool balance")
+        }
+        put_pool(&env, pool - amount);
+
+        let reward_id: RewardId = env
+            .storage()
+            .instance()
+            .get(&DataKey::RewardCount)
+            .unwrap_or(0)
+            + 1;
+        env.storage().instance().set(&DataKey::RewardCount, &reward_id);
+
+        let now = env.ledger().timestamp();
+        put_reward(
+            &env,
+            &Reward {
+                id: reward_id,
+                whistleblower: whistleblower.clone(),
+                deal_id,
+                listing_id,
+                amount,
+                status: RewardStatus::Pending,
+                created_at: now,
+                updated_at: now,
+                paid_at: None,
+                cancel_reason: None,
+            },
+        );
+
+        env.events().publish(
+            (Symbol::new(&env, "reward_created"), reward_id),
+            (whistleblower, deal_id, listing_id, amount),
+        );
+        reward_id
+    }
+
+    /// Make a pending reward payable once its deal has gone live
+    ///
+    /// Anyone can call this. The deal must be linked to the reward's listing
+    /// in rent_payments and be active (or already completed).
+    pub fn activate(env: Env, reward_id: RewardId) {
+        let mut reward = get_reward(&env, reward_id);
+        if reward.status != RewardStatus::Pending {
+            panic!("reward is not pending")
+        }
+
+        let deals_contract: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::DealsContract)
+            .unwrap();
+        let deals = DealsClient::new(&env, &deals_contract);
+        if deals.deal_listing(&reward.deal_id) != Some(reward.listing_id) {
+            panic!("deal is not linked to the listing")
+        }
+        if deals.deal_status(&reward.deal_id) == DealStatus::Defaulted {
+            panic!("deal is not active")
+        }
+
+        reward.status = RewardStatus::Payable;
+        reward.updated_at = env.ledger().timestamp();
+        put_reward(&env, &reward);
+        env.events()
+            .publish((Symbol::new(&env, "reward_payable"), reward_id), reward.deal_id);
+    }
+
+    /// Pay a payable reward out to its whistleblower (whistleblower-auth)
+    pub fn claim(env: Env, reward_id: RewardId) {
+        let mut reward = get_reward(&env, reward_id);
+        reward.whistleblower.require_auth();
+        if reward.status != RewardStatus::Payable {
+            panic!("reward is not payable")
+        }
+
+        let now = env.ledger().timestamp();
+        reward.status = RewardStatus::Paid;
+        reward.updated_at = now;
+        reward.paid_at = Some(now);
+        put_reward(&env, &reward);
+        token_client(&env).transfer(
+            &env.current_contract_address(),
+            &reward.whistleblower,
+            &reward.amount,
+        );
+
+        env.events().publish(
+            (Symbol::new(&env, "reward_paid"), reward_id),
+            (reward.whistleblower, reward.amount),
+        );
+    }
+
+    /// Cancel an unpaid reward, returning its escrow to the pool (admin-auth)
+    pub fn cancel_reward(env: Env, reward_id: RewardId, reason: Symbol) {
+        require_admin(&env);
+        let mut reward = get_reward(&env, reward_id);
+        if !matches!(reward.status, RewardStatus::Pending | RewardStatus::Payable) {
+            panic!("reward cannot be cancelled")
+        }
+
+        reward.status = RewardStatus::Cancelled;
+        reward.updated_at = env.ledger().timestamp();
+        reward.cancel_reason = Some(reason.clone());
+        put_reward(&env, &reward);
+        put_pool(&env, get_pool(&env) + reward.amount);
+
+        env.events()
+            .publish((Symbol::new(&env, "reward_cancelled"), reward_id), reason);
+    }
+
+    pub fn get_reward(env: Env, reward_id: RewardId) -> Reward {
+        get_reward(&env, reward_id)
+    }
+
+    /// Funds available for new rewards
+    pub fn pool_balance(env: Env) -> i128 {
+        get_pool(&env)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::{DealStatus, RewardStatus, Rewards, RewardsClient};
+    use soroban_sdk::testutils::{Address as _, Events, MockAuth, MockAuthInvoke};
+    use soroban_sdk::token::{Client as TokenClient, StellarAssetClient};
+    use soroban_sdk::{contract, contractimpl, Address, Env, IntoVal, Symbol};
+
+    /// Stand-in for rent_payments with a single deal
+    #[contract]
+    struct MockDeals;
+
+    #[contractimpl]
+    impl MockDeals {
+        pub fn set_deal(env: Env, status: DealStatus, listing_id: Option<u64>) {
+            env.storage().instance().set(&Symbol::new(&env, "status"), &status);
+            env.storage().instance().set(&Symbol::new(&env, "listing"), &listing_id);
+        }
+
+        pub fn deal_status(env: Env, _deal_id: u64) -> DealStatus {
+            env.storage().instance().get(&Symbol::new(&env, "status")).unwrap()
+        }
+
+        pub fn deal_listing(env: Env, _deal_id: u64) -> Option<u64> {
+            env.storage().instance().get(&Symbol::new(&env, "listing")).unwrap()
+        }
+    }
+
+    /// Rewards contract with a 1000 pool and one 100 reward for deal 1 / listing 7;
+    /// returns the contract, its admin, the token, the deals stand-in and the
+    /// whistleblower
+    fn setup(
+        env: &Env,
+    ) -> (RewardsClient<'_>, Address, TokenClient<'_>, MockDealsClient<'_>, Address) {
+        let admin = Address::generate(env);
+        let token_id = env
+            .register_stellar_asset_contract_v2(Address::generate(env))
+            .address();
+        let deals_id = env.register(MockDeals, ());
+        let contract_id = env.register(Rewards, ());
+        let client = RewardsClient::new(env, &contract_id);
+        let whistleblower = Address::generate(env);
+
+        env.mock_all_auths();
+        client.init(&admin, &token_id, &deals_id);
+        let funder = Address::generate(env);
+        StellarAssetClient::new(env, &token_id).mint(&funder, &1000);
+        client.fund_pool(&funder, &1000);
+        client.create_reward(&whistleblower, &1u64, &7u64, &100);
+
+        let token = TokenClient::new(env, &token_id);
+        (client, admin, token, MockDealsClient::new(env, &deals_id), whistleblower)
+    }
+
+    // ============================================================================
+    // Funding Tests
+    // ============================================================================
+
+    #[test]
+    fn create_reward_escrows_from_pool() {
+        let env = Env::default();
+        let (client, _admin, token, _deals, whistleblower) = setup(&env);
+
+        let reward = client.get_reward(&1);
+        assert_eq!(reward.status, RewardStatus::Pending);
+        assert_eq!(reward.amount, 100);
+        assert_eq!(reward.whistleblower, whistleblower);
+        assert_eq!(client.pool_balance(), 900);
+        assert_eq!(token.balance(&client.address), 1000);
+    }
+
+    #[test]
+    #[should_panic(expected = "insufficient pool balance")]
+    fn create_reward_requires_pool_funds() {
+        let env = Env::default();
+        let (client, _admin, _token, _deals, whistleblower) = setup(&env);
+
+        client.create_reward(&whistleblower, &2u64, &8u64, &901);
+    }
+
+    #[test]
+    #[should_panic]
+    fn create_reward_requires_admin() {
+        let env = Env::default();
+        let (client, _admin, _token, _deals, whistleblower) = setup(&env);
+
+        env.mock_auths(&[MockAuth {
+            address: &whistleblower,
+            invoke: &MockAuthInvoke {
+                contract: &client.address,
+                fn_name: "create_reward",
+                args: (whistleblower.clone(), 2u64, 8u64, 50i128).into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+        client.create_reward(&whistleblower, &2u64, &8u64, &50);
+    }
+
+    // ============================================================================
+    // Activation and Claim Tests
+    // ============================================================================
+
+    #[test]
+    fn claim_pays_whistleblower_after_activation() {
+        let env = Env::default();
+        let (client, _admin, token, deals, whistleblower) = setup(&env);
+        deals.set_deal(&DealStatus::Active, &Some(7));
+
+        client.activate(&1);
+        assert_eq!(client.get_reward(&1).status, RewardStatus::Payable);
+
+        env.mock_auths(&[MockAuth {
+            address: &whistleblower,
+            invoke: &MockAuthInvoke {
+                contract: &client.address,
+                fn_name: "claim",
+                args: (1u64,).into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+        client.claim(&1);
+
+        let reward = client.get_reward(&1);
+        assert_eq!(reward.status, RewardStatus::Paid);
+        assert!(reward.paid_at.is_some());
+        assert_eq!(token.balance(&whistleblower), 100);
+        assert_eq!(token.balance(&client.address), 900);
+    }
+
+    #[test]
+    #[should_panic(expected = "deal is not linked to the listing")]
+    fn activate_requires_linked_listing() {
+        let env = Env::default();
+        let (client, _admin, _token, deals, _whistleblower) = setup(&env);
+        deals.set_deal(&DealStatus::Active, &Some(8));
+
+        client.activate(&1);
+    }
+
+    #[test]
+    #[should_panic(expected = "deal is not active")]
+    fn activate_rejects_defaulted_deal() {
+        let env = Env::default();
+        let (client, _admin, _token, deals, _whistleblower) = setup(&env);
+        deals.set_deal(&DealStatus::Defaulted, &Some(7));
+
+        client.activate(&1);
+    }
+
+    #[test]
+    #[should_panic(expected = "reward is not payable")]
+    fn claim_requires_payable_reward() {
+        let env = Env::default();
+        let (client, _admin, _token, _deals, _whistleblower) = setup(&env);
+
+        client.claim(&1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn claim_requires_whistleblower_auth() {
+        let env = Env::default();
+        let (client, admin, _token, deals, _whistleblower) = setup(&env);
+        deals.set_deal(&DealStatus::Active, &Some(7));
+        client.activate(&1);
+
+        env.mock_auths(&[MockAuth {
+            address: &admin,
+            invoke: &MockAuthInvoke {
+                contract: &client.address,
+                fn_name: "claim",
+                args: (1u64,).into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+        client.claim(&1);
+    }
+
+    // ============================================================================
+    // Cancellation Tests
+    // ============================================================================
+
+    #[test]
+    fn cancel_reward_returns_escrow_with_reason() {
+        let env = Env::default();
+        let (client, _admin, _token, _deals, _whistleblower) = setup(&env);
+        let reason = Symbol::new(&env, "fraudulent");
+
+        client.cancel_reward(&1, &reason);
+
+        let event = env.events().all().last().unwrap();
+        assert_eq!(
+            event.1,
+            (Symbol::new(&env, "reward_cancelled"), 1u64).into_val(&env)
+        );
+        let reward = client.get_reward(&1);
+        assert_eq!(reward.status, RewardStatus::Cancelled);
+        assert_eq!(reward.cancel_reason, Some(reason));
+        assert_eq!(client.pool_balance(), 1000);
+    }
+
+    #[test]
+    #[should_panic(expected = "reward cannot be cancelled")]
+    fn paid_reward_cannot_be_cancelled() {
+        let env = Env::default();
+        let (client, _admin, _token, deals, _whistleblower) = setup(&env);
+        deals.set_deal(&DealStatus::Completed, &Some(7));
+        client.activate(&1);
+        client.claim(&1);
+
+        client.cancel_reward(&1, &Symbol::new(&env, "late"));
+    }
+}